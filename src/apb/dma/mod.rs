@@ -461,12 +461,12 @@ impl Channel {
     /// For transfer status details, see [`Channel::is_busy`].
     ///
     /// NOTE: This method has a strong low-level approach and
-    /// shouldn't be called directly.
+    /// is only meant to be used by device drivers.
     ///
     /// [`Channel::start`]: struct.Channel.html#method.start
     /// [`Channel::finish`]: struct.Channel.html#method.finish
     /// [`Channel::is_busy`]: struct.Channel.html#method.is_busy
-    pub(super) fn query(
+    pub(crate) fn query(
         &self,
         slave: u32,
         ahb_address: u32,
@@ -483,10 +483,11 @@ impl Channel {
         register_base.APBDMACHAN_CHANNEL_AHB_PTR_0.set(ahb_address);
         register_base.APBDMACHAN_CHANNEL_APB_PTR_0.set(apb_address);
 
-        // Set AHB 1 word burst, and no address wrapping.
+        // Set AHB 1 word burst, no address wrapping, and the default address reload.
         register_base.APBDMACHAN_CHANNEL_AHB_SEQ_0.modify(
             APBDMACHAN_CHANNEL_AHB_SEQ_0::AHB_BURST::DmaBurst1Words
             + APBDMACHAN_CHANNEL_AHB_SEQ_0::AHB_ADDR_WRAP::NoWrap
+            + APBDMACHAN_CHANNEL_AHB_SEQ_0::DBL_BUF::ReloadFor1XBlocks
         );
 
         // Set APB bus width, and address wrap for each word.
@@ -500,10 +501,12 @@ impl Channel {
 
         // Set transfer mode to one block at a time (64kB),
         // set DMA direction for AHB to read,
+        // disable the end-of-chain interrupt a previous stream may have left enabled,
         // and set up flow control.
         register_base.APBDMACHAN_CHANNEL_CSR_0.modify(
             APBDMACHAN_CHANNEL_CSR_0::ONCE::SingleBlock
             + APBDMACHAN_CHANNEL_CSR_0::DIR::AhbRead
+            + APBDMACHAN_CHANNEL_CSR_0::IE_EOC::CLEAR
             + APBDMACHAN_CHANNEL_CSR_0::REQ_SEL.val(slave)
             + APBDMACHAN_CHANNEL_CSR_0::FLOW::SET
         );
@@ -520,12 +523,12 @@ impl Channel {
     /// For transfer status details, see [`Channel::is_busy`].
     ///
     /// NOTE: This method has a strong low-level approach and
-    /// shouldn't be called directly.
+    /// is only meant to be used by device drivers.
     ///
     /// [`Channel::start`]: struct.Channel.html#method.start
     /// [`Channel::finish`]: struct.Channel.html#method.finish
     /// [`Channel::is_busy`]: struct.Channel.html#method.is_busy
-    pub(super) fn write(
+    pub(crate) fn write(
         &self,
         slave: u32,
         ahb_address: u32,
@@ -542,10 +545,11 @@ impl Channel {
         register_base.APBDMACHAN_CHANNEL_AHB_PTR_0.set(ahb_address);
         register_base.APBDMACHAN_CHANNEL_APB_PTR_0.set(apb_address);
 
-        // Set AHB 1 word burst, and no address wrapping.
+        // Set AHB 1 word burst, no address wrapping, and the default address reload.
         register_base.APBDMACHAN_CHANNEL_AHB_SEQ_0.modify(
             APBDMACHAN_CHANNEL_AHB_SEQ_0::AHB_BURST::DmaBurst1Words
             + APBDMACHAN_CHANNEL_AHB_SEQ_0::AHB_ADDR_WRAP::NoWrap
+            + APBDMACHAN_CHANNEL_AHB_SEQ_0::DBL_BUF::ReloadFor1XBlocks
         );
 
         // Set APB bus width, and address wrap for each word.
@@ -558,15 +562,117 @@ impl Channel {
         register_base.APBDMACHAN_CHANNEL_WCOUNT_0.set((size - 1) as u32);
 
         // Set transfer mode to one block at a time (64kB),
-        // set DMA direction for AHB to read,
+        // set DMA direction for AHB to write,
+        // disable the end-of-chain interrupt a previous stream may have left enabled,
         // and set up flow control.
         register_base.APBDMACHAN_CHANNEL_CSR_0.modify(
             APBDMACHAN_CHANNEL_CSR_0::ONCE::SingleBlock
             + APBDMACHAN_CHANNEL_CSR_0::DIR::AhbWrite
+            + APBDMACHAN_CHANNEL_CSR_0::IE_EOC::CLEAR
             + APBDMACHAN_CHANNEL_CSR_0::REQ_SEL.val(slave)
             + APBDMACHAN_CHANNEL_CSR_0::FLOW::SET
         );
 
         Ok(())
     }
+
+    /// Prepares the channel for continuous transfers of blocks of data.
+    ///
+    /// This works like [`Channel::query`] and [`Channel::write`], depending on
+    /// `ahb_read`, except that the channel runs in multiple block mode. Once a
+    /// block of `size` words is done, the end-of-chain flag is raised and the
+    /// next block is started right away from the address that was last passed
+    /// to [`Channel::set_next_address`], or from the same address otherwise.
+    ///
+    /// NOTE: This method has a strong low-level approach and
+    /// is only meant to be used by device drivers.
+    ///
+    /// [`Channel::query`]: struct.Channel.html#method.query
+    /// [`Channel::write`]: struct.Channel.html#method.write
+    /// [`Channel::set_next_address`]: struct.Channel.html#method.set_next_address
+    pub(crate) fn stream(
+        &self,
+        slave: u32,
+        ahb_address: u32,
+        apb_address: u32,
+        size: u32,
+        ahb_read: bool,
+    ) -> Result<(), ()> {
+        let register_base = unsafe { &*self.registers };
+
+        if size == 0 {
+            return Err(());
+        }
+
+        // Program AHB and APB Starting addresses.
+        register_base.APBDMACHAN_CHANNEL_AHB_PTR_0.set(ahb_address);
+        register_base.APBDMACHAN_CHANNEL_APB_PTR_0.set(apb_address);
+
+        // Set AHB 1 word burst, no address wrapping, and reload the address for every block.
+        register_base.APBDMACHAN_CHANNEL_AHB_SEQ_0.modify(
+            APBDMACHAN_CHANNEL_AHB_SEQ_0::AHB_BURST::DmaBurst1Words
+            + APBDMACHAN_CHANNEL_AHB_SEQ_0::AHB_ADDR_WRAP::NoWrap
+            + APBDMACHAN_CHANNEL_AHB_SEQ_0::DBL_BUF::ReloadFor1XBlocks
+        );
+
+        // Set APB bus width, and address wrap for each word.
+        register_base.APBDMACHAN_CHANNEL_APB_SEQ_0.modify(
+            APBDMACHAN_CHANNEL_APB_SEQ_0::APB_BUS_WIDTH::BusWidth32
+            + APBDMACHAN_CHANNEL_APB_SEQ_0::APB_ADDR_WRAP::WrapOn1Words
+        );
+
+        // Set the amount of words to be transferred per block.
+        register_base.APBDMACHAN_CHANNEL_WCOUNT_0.set((size - 1) as u32);
+
+        // Set transfer mode to multiple blocks,
+        // set DMA direction for AHB,
+        // raise the end-of-chain flag after every block,
+        // and set up flow control.
+        let direction = if ahb_read {
+            APBDMACHAN_CHANNEL_CSR_0::DIR::AhbRead
+        } else {
+            APBDMACHAN_CHANNEL_CSR_0::DIR::AhbWrite
+        };
+        register_base.APBDMACHAN_CHANNEL_CSR_0.modify(
+            APBDMACHAN_CHANNEL_CSR_0::ONCE::MultipleBlock
+            + direction
+            + APBDMACHAN_CHANNEL_CSR_0::IE_EOC::SET
+            + APBDMACHAN_CHANNEL_CSR_0::REQ_SEL.val(slave)
+            + APBDMACHAN_CHANNEL_CSR_0::FLOW::SET
+        );
+
+        Ok(())
+    }
+
+    /// Sets the AHB address the next block of a continuous transfer starts from.
+    ///
+    /// While the channel is running, the address is latched at the start of
+    /// the next block, so the block that is currently in progress is not
+    /// affected.
+    ///
+    /// NOTE: This method has a strong low-level approach and
+    /// is only meant to be used by device drivers.
+    pub(crate) fn set_next_address(&self, ahb_address: u32) {
+        let register_base = unsafe { &*self.registers };
+
+        register_base.APBDMACHAN_CHANNEL_AHB_PTR_0.set(ahb_address);
+    }
+
+    /// Checks whether a block of a continuous transfer was completed and acknowledges it.
+    ///
+    /// This reads the `ISE_EOC` flag of `APBDMACHAN_CHANNEL_STA_0` and clears it
+    /// by writing `1` back, which also deasserts the interrupt of the channel.
+    pub fn take_block_done(&self) -> bool {
+        let register_base = unsafe { &*self.registers };
+
+        if register_base.APBDMACHAN_CHANNEL_STA_0.is_set(APBDMACHAN_CHANNEL_STA_0::ISE_EOC) {
+            register_base
+                .APBDMACHAN_CHANNEL_STA_0
+                .write(APBDMACHAN_CHANNEL_STA_0::ISE_EOC::SET);
+
+            true
+        } else {
+            false
+        }
+    }
 }
@@ -71,9 +71,28 @@ pub mod misc_pp {
 }
 
 pub mod misc_gp {
-    use register::{mmio::*, register_structs};
+    use register::{mmio::*, register_bitfields, register_structs};
+
+    // TODO: Bitfields of the SDMMC and EMMC pad registers.
+
+    register_bitfields! {
+        u32,
 
-    // TODO: Bitfields.
+        /// Bitfields of the `APB_MISC_GP_*_CFGPADCTRL_0` registers of the regular pads.
+        pub APB_MISC_GP_CFGPADCTRL_0 [
+            /// The falling edge slew rate of the pad.
+            CFG2TMC_SLWF OFFSET(30) NUMBITS(2) [],
+
+            /// The rising edge slew rate of the pad.
+            CFG2TMC_SLWR OFFSET(28) NUMBITS(2) [],
+
+            /// The pull-up drive strength of the pad.
+            CFG2TMC_DRVUP OFFSET(20) NUMBITS(5) [],
+
+            /// The pull-down drive strength of the pad.
+            CFG2TMC_DRVDN OFFSET(12) NUMBITS(5) []
+        ]
+    }
 
     register_structs! {
         /// Representation of the APB Pad Control Registers.
@@ -0,0 +1,138 @@
+//! Data cache maintenance for buffers shared with DMA engines.
+//!
+//! # Description
+//!
+//! DMA engines of the Tegra X1, such as the APB DMA controller, access
+//! physical memory directly and are not coherent with the data caches of
+//! the CPU. Buffers that are handed to such an engine need to be maintained
+//! by address:
+//!
+//! - Before the engine reads a buffer, it must be cleaned through
+//!   [`clean_dcache_range`], so data that is still held in the cache is
+//!   written back to memory.
+//! - After the engine wrote a buffer, it must be invalidated through
+//!   [`invalidate_dcache_range`], so the CPU doesn't read stale data
+//!   from the cache.
+//!
+//! Maintenance operates on whole cache lines of [`CACHE_LINE_SIZE`] bytes.
+//! Buffers that are written by a DMA engine should therefore be aligned to
+//! and padded to a multiple of the cache line size, otherwise invalidating
+//! them discards writes of the CPU to data that shares the first or last line.
+//!
+//! NOTE: The maintenance instructions only exist on AArch64. On other
+//! targets, e.g. when building for the host, these functions do nothing.
+//!
+//! [`clean_dcache_range`]: fn.clean_dcache_range.html
+//! [`invalidate_dcache_range`]: fn.invalidate_dcache_range.html
+//! [`CACHE_LINE_SIZE`]: constant.CACHE_LINE_SIZE.html
+
+#[cfg(target_arch = "aarch64")]
+use cortex_a::barrier;
+
+/// The size of a data cache line of the Cortex-A57 cores, in bytes.
+pub const CACHE_LINE_SIZE: usize = 64;
+
+/// Cleans the data cache lines that cover a given memory range to the point of coherency.
+///
+/// Dirty lines are written back to memory and stay valid in the cache.
+pub fn clean_dcache_range(address: usize, length: usize) {
+    match () {
+        #[cfg(target_arch = "aarch64")]
+        () => {
+            for line in lines(address, length) {
+                unsafe {
+                    asm!("dc cvac, $0" :: "r"(line) : "memory" : "volatile");
+                }
+            }
+
+            unsafe {
+                barrier::dsb(barrier::SY);
+            }
+        }
+
+        #[cfg(not(target_arch = "aarch64"))]
+        () => {
+            let _ = (address, length);
+        }
+    }
+}
+
+/// Cleans and invalidates the data cache lines that cover a given memory range.
+///
+/// Dirty lines are written back to memory before they are dropped from the cache.
+pub fn clean_invalidate_dcache_range(address: usize, length: usize) {
+    match () {
+        #[cfg(target_arch = "aarch64")]
+        () => {
+            for line in lines(address, length) {
+                unsafe {
+                    asm!("dc civac, $0" :: "r"(line) : "memory" : "volatile");
+                }
+            }
+
+            unsafe {
+                barrier::dsb(barrier::SY);
+            }
+        }
+
+        #[cfg(not(target_arch = "aarch64"))]
+        () => {
+            let _ = (address, length);
+        }
+    }
+}
+
+/// Invalidates the data cache lines that cover a given memory range.
+///
+/// NOTE: Dirty lines are dropped without being written back, which includes
+/// data outside of the range that shares its first or last cache line.
+pub fn invalidate_dcache_range(address: usize, length: usize) {
+    match () {
+        #[cfg(target_arch = "aarch64")]
+        () => {
+            for line in lines(address, length) {
+                unsafe {
+                    asm!("dc ivac, $0" :: "r"(line) : "memory" : "volatile");
+                }
+            }
+
+            unsafe {
+                barrier::dsb(barrier::SY);
+            }
+        }
+
+        #[cfg(not(target_arch = "aarch64"))]
+        () => {
+            let _ = (address, length);
+        }
+    }
+}
+
+/// Gets the addresses of the cache lines that cover a given memory range.
+#[cfg_attr(not(target_arch = "aarch64"), allow(dead_code))]
+fn lines(address: usize, length: usize) -> impl Iterator<Item = usize> {
+    let start = address & !(CACHE_LINE_SIZE - 1);
+    let end = address + length;
+
+    (start..end).step_by(CACHE_LINE_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_cover_unaligned_ranges() {
+        let mut lines = lines(0x1030, 0x50);
+
+        assert_eq!(lines.next(), Some(0x1000));
+        assert_eq!(lines.next(), Some(0x1040));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn lines_of_aligned_ranges() {
+        assert_eq!(lines(0x2000, 2 * CACHE_LINE_SIZE).count(), 2);
+        assert_eq!(lines(0x2000, 0).count(), 0);
+    }
+}
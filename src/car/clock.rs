@@ -34,6 +34,10 @@ const CLK_RST_CONTROLLER_CLK_SOURCE_I2C_3: u32 = 0x1B8;
 const CLK_RST_CONTROLLER_CLK_SOURCE_I2C_4: u32 = 0x3C4;
 const CLK_RST_CONTROLLER_CLK_SOURCE_I2C_5: u32 = 0x128;
 const CLK_RST_CONTROLLER_CLK_SOURCE_I2C_6: u32 = 0x65C;
+const CLK_RST_CONTROLLER_CLK_SOURCE_SPI_1: u32 = 0x134;
+const CLK_RST_CONTROLLER_CLK_SOURCE_SPI_2: u32 = 0x118;
+const CLK_RST_CONTROLLER_CLK_SOURCE_SPI_3: u32 = 0x1BC;
+const CLK_RST_CONTROLLER_CLK_SOURCE_SPI_4: u32 = 0x1B4;
 const CLK_RST_CONTROLLER_CLK_SOURCE_SE: u32 = 0x42C;
 const CLK_RST_CONTROLLER_CLK_SOURCE_HOST1X: u32 = 0x180;
 const CLK_RST_CONTROLLER_CLK_SOURCE_TSEC: u32 = 0x1F4;
@@ -172,6 +176,46 @@ impl Clock {
         clock_divisor: 0,
     };
 
+    /// Representation of the SPI 1 clock.
+    pub const SPI_1: Self = Clock {
+        reset: CLK_RST_CONTROLLER_RST_DEVICES_H,
+        enable: CLK_RST_CONTROLLER_CLK_OUT_ENB_H,
+        source: CLK_RST_CONTROLLER_CLK_SOURCE_SPI_1,
+        index: 0x9,
+        clock_source: 0,
+        clock_divisor: 0x1E,
+    };
+
+    /// Representation of the SPI 2 clock.
+    pub const SPI_2: Self = Clock {
+        reset: CLK_RST_CONTROLLER_RST_DEVICES_H,
+        enable: CLK_RST_CONTROLLER_CLK_OUT_ENB_H,
+        source: CLK_RST_CONTROLLER_CLK_SOURCE_SPI_2,
+        index: 0xC,
+        clock_source: 0,
+        clock_divisor: 0x1E,
+    };
+
+    /// Representation of the SPI 3 clock.
+    pub const SPI_3: Self = Clock {
+        reset: CLK_RST_CONTROLLER_RST_DEVICES_H,
+        enable: CLK_RST_CONTROLLER_CLK_OUT_ENB_H,
+        source: CLK_RST_CONTROLLER_CLK_SOURCE_SPI_3,
+        index: 0xE,
+        clock_source: 0,
+        clock_divisor: 0x1E,
+    };
+
+    /// Representation of the SPI 4 clock.
+    pub const SPI_4: Self = Clock {
+        reset: CLK_RST_CONTROLLER_RST_DEVICES_U,
+        enable: CLK_RST_CONTROLLER_CLK_OUT_ENB_U,
+        source: CLK_RST_CONTROLLER_CLK_SOURCE_SPI_4,
+        index: 0x4,
+        clock_source: 0,
+        clock_divisor: 0x1E,
+    };
+
     /// Representation of the Security Engine clock.
     pub const SE: Self = Clock {
         reset: CLK_RST_CONTROLLER_RST_DEVICES_V,
@@ -450,6 +494,33 @@ impl Clock {
         assert!(self.is_enabled());
     }
 
+    /// Reprograms the divisor of the clock, keeping its clock source.
+    ///
+    /// NOTE: This has no effect on clocks without a source register.
+    pub fn set_divisor(&self, divisor: u32) {
+        if self.source != CLK_NO_SOURCE {
+            unsafe {
+                (*((CAR + self.source) as *const ReadWrite<u32>))
+                    .set((self.clock_source << 29) | divisor);
+            }
+        }
+    }
+
+    /// Reprograms the clock source and the divisor of the clock.
+    ///
+    /// The source is the value of the source selection field, whose
+    /// meaning depends on the device. The default source is restored
+    /// when the device is enabled again.
+    ///
+    /// NOTE: This has no effect on clocks without a source register.
+    pub fn set_source(&self, source: u32, divisor: u32) {
+        if self.source != CLK_NO_SOURCE {
+            unsafe {
+                (*((CAR + self.source) as *const ReadWrite<u32>)).set((source << 29) | divisor);
+            }
+        }
+    }
+
     /// Disables the device.
     pub fn disable(&self) {
         // Put clock into reset.
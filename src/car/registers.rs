@@ -0,0 +1,48 @@
+//! Abstractions over the Clock and Reset Controller registers.
+//!
+//! See Chapter 5.2 in the Tegra X1 Technical Reference Manual for details.
+
+use register::{mmio::ReadWrite, register_bitfields};
+
+use crate::memory_map::CAR;
+
+/// A pointer to the `CLK_RST_CONTROLLER_OSC_CTRL_0` register, to be accessed by dereferencing it.
+pub const OSC_CTRL: *const ReadWrite<u32, CLK_RST_CONTROLLER_OSC_CTRL_0::Register> =
+    (CAR + 0x50) as *const _;
+
+register_bitfields! {
+    u32,
+
+    /// Bitfields of the `CLK_RST_CONTROLLER_OSC_CTRL_0` register.
+    pub CLK_RST_CONTROLLER_OSC_CTRL_0 [
+        /// The frequency of the main oscillator, which drives clk_m.
+        OSC_FREQ OFFSET(28) NUMBITS(4) [
+            /// 13 MHz.
+            Osc13 = 0,
+            /// 16.8 MHz.
+            Osc16p8 = 1,
+            /// 19.2 MHz.
+            Osc19p2 = 4,
+            /// 38.4 MHz.
+            Osc38p4 = 5,
+            /// 12 MHz.
+            Osc12 = 8,
+            /// 48 MHz.
+            Osc48 = 9,
+            /// 26 MHz.
+            Osc26 = 12
+        ],
+
+        /// The divider that is applied to the oscillator for pll_ref.
+        PLL_REF_DIV OFFSET(26) NUMBITS(2) [],
+
+        /// The drive strength of the oscillator pad.
+        XOFS OFFSET(4) NUMBITS(6) [],
+
+        /// Whether the oscillator is bypassed by an external clock.
+        XOBP OFFSET(2) NUMBITS(1) [],
+
+        /// Whether the crystal oscillator is enabled.
+        XOE OFFSET(0) NUMBITS(1) []
+    ]
+}
@@ -12,6 +12,7 @@ use super::registers::*;
 /// Enumeration of potential errors that may occur
 /// during communication over the I²C protocol.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error {
     /// Generic I²C error. Not closer specified.
     Generic,
@@ -1,5 +1,6 @@
-#![no_std]
+#![cfg_attr(not(any(test, feature = "mock")), no_std)]
 #![recursion_limit = "256"]
+#![cfg_attr(target_arch = "aarch64", feature(asm))]
 #![feature(const_fn)]
 #![feature(optimize_attribute)]
 
@@ -8,6 +9,8 @@ pub extern crate cortex_a;
 #[macro_use]
 extern crate enum_primitive;
 
+extern crate nb;
+
 extern crate paste;
 
 extern crate register;
@@ -15,7 +18,10 @@ extern crate register;
 #[macro_use]
 extern crate static_assertions;
 
+extern crate void;
+
 pub mod apb;
+pub mod cache;
 pub mod car;
 pub mod flow;
 pub mod gpio;
@@ -40,7 +40,31 @@
 //! PinGrP::PwrI2CSdaPy4.set_io(pinmux::PinIo::Input);
 //! ```
 //!
+//! Every method performs a read-modify-write of the `PINMUX_AUX_*` register
+//! of the pad, so the remaining fields are preserved. This makes it possible
+//! to configure a single aspect of an arbitrary pad, e.g. to hand it over to
+//! a [`Gpio`]:
+//!
+//! ```no_run
+//! use libtegra::pinmux::{PinGrP, PinIo, PinPull, PinTristate};
+//!
+//! PinGrP::Spi4SckPc5.set_tristate(PinTristate::Passthrough);
+//! PinGrP::Spi4SckPc5.set_pull(PinPull::Up);
+//! PinGrP::Spi4SckPc5.set_io(PinIo::Output);
+//! ```
+//!
+//! For peripherals with a fixed set of pads, helpers such as [`configure_spi`]
+//! apply the complete configuration for a controller at once.
+//!
+//! ```no_run
+//! use libtegra::{pinmux, spi::Spi};
+//!
+//! pinmux::configure_spi(pinmux::SpiController::Spi4);
+//! Spi::SPI4.init();
+//! ```
+//!
 //! [`Gpio`]: ../gpio/struct.Gpio.html
+//! [`configure_spi`]: fn.configure_spi.html
 //! [`PinFunction`]: enum.PinFunction.html
 //! [`PinGrP`]: enum.PinGrP.html
 //! [`PinFunction`]: enum.PinFunction.html
@@ -54,6 +78,8 @@
 
 use register::mmio::ReadWrite;
 
+use crate::apb::misc::{self, misc_gp::APB_MISC_GP_CFGPADCTRL_0};
+
 pub use registers::*;
 
 mod registers;
@@ -376,6 +402,87 @@ pub enum PinEIoHv {
     High,
 }
 
+/// Enumeration of potential errors that may occur when tuning the electrical
+/// characteristics of a pad.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PadControlError {
+    /// The pad has no regular pad control register, e.g. because it is
+    /// controlled as part of an SDMMC or QSPI pad group.
+    Unsupported,
+    /// A value exceeds the width of its field.
+    InvalidValue,
+}
+
+/// Enumeration over the SPI controllers with dedicated pads.
+///
+/// NOTE: SPI3 is not listed as it has no dedicated pads and can only be
+/// routed through alternate functions of other pin groups, which is
+/// board-specific and has to be configured manually.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpiController {
+    /// The SPI1 controller.
+    Spi1,
+    /// The SPI2 controller.
+    Spi2,
+    /// The SPI4 controller.
+    Spi4,
+}
+
+/// Configures the SCK, MOSI, MISO and CS0 pads of a given SPI controller.
+///
+/// All pads are switched to the SFIO function of the controller, have their
+/// output drivers enabled and their input receivers enabled. The CS0 pad is
+/// pulled up so the line stays inactive while the controller is not driving
+/// it, the remaining pads are left floating.
+///
+/// This has to be called before [`Spi::init`] for the controller to be able
+/// to drive the bus.
+///
+/// [`Spi::init`]: ../spi/struct.Spi.html#method.init
+pub fn configure_spi(controller: SpiController) {
+    let (function, sck, mosi, miso, cs) = match controller {
+        SpiController::Spi1 => (
+            PinFunction::Spi1,
+            PinGrP::Spi1SckPc2,
+            PinGrP::Spi1MosiPc0,
+            PinGrP::Spi1MisoPc1,
+            PinGrP::Spi1Cs0Pc3,
+        ),
+        SpiController::Spi2 => (
+            PinFunction::Spi2,
+            PinGrP::Spi2SckPb6,
+            PinGrP::Spi2MosiPb4,
+            PinGrP::Spi2MisoPb5,
+            PinGrP::Spi2Cs0Pb7,
+        ),
+        SpiController::Spi4 => (
+            PinFunction::Spi4,
+            PinGrP::Spi4SckPc5,
+            PinGrP::Spi4MosiPc7,
+            PinGrP::Spi4MisoPd0,
+            PinGrP::Spi4Cs0Pc6,
+        ),
+    };
+
+    for &(pin, pull) in &[
+        (sck, PinPull::None),
+        (mosi, PinPull::None),
+        (miso, PinPull::None),
+        (cs, PinPull::Up),
+    ] {
+        pin.config(
+            function,
+            pull,
+            PinTristate::Passthrough,
+            PinIo::Input,
+            PinLock::Default,
+            PinOd::Default,
+            PinEIoHv::Default,
+        );
+    }
+}
+
 /// Representation of a pin group of the SoC, including the respective functions.
 #[derive(Debug)]
 struct SocPinGrP(PinGrP, [PinFunction; 4]);
@@ -2027,6 +2134,206 @@ impl PinGrP {
         register.set(value);
     }
 
+    /// Gets the `APB_MISC_GP_*_CFGPADCTRL_0` register of this Pin Group, if it has one.
+    fn pad_control(self) -> Option<&'static ReadWrite<u32, APB_MISC_GP_CFGPADCTRL_0::Register>> {
+        let gp = unsafe { &(*misc::REGISTERS).gp };
+
+        let register = match self {
+            PinGrP::PexL0RstNPa0 => &gp.APB_MISC_GP_PEX_L0_RST_N_CFGPADCTRL_0,
+            PinGrP::PexL0ClkreqNPa1 => &gp.APB_MISC_GP_PEX_L0_CLKREQ_N_CFGPADCTRL_0,
+            PinGrP::PexWakeNPa2 => &gp.APB_MISC_GP_PEX_WAKE_N_CFGPADCTRL_0,
+            PinGrP::PexL1RstNPa3 => &gp.APB_MISC_GP_PEX_L1_RST_N_CFGPADCTRL_0,
+            PinGrP::PexL1ClkreqNPa4 => &gp.APB_MISC_GP_PEX_L1_CLKREQ_N_CFGPADCTRL_0,
+            PinGrP::SataLedActivePa5 => &gp.APB_MISC_GP_SATA_LED_ACTIVE_CFGPADCTRL_0,
+            PinGrP::Spi1MosiPc0 => &gp.APB_MISC_GP_SPI1_MOSI_CFGPADCTRL_0,
+            PinGrP::Spi1MisoPc1 => &gp.APB_MISC_GP_SPI1_MISO_CFGPADCTRL_0,
+            PinGrP::Spi1SckPc2 => &gp.APB_MISC_GP_SPI1_SCK_CFGPADCTRL_0,
+            PinGrP::Spi1Cs0Pc3 => &gp.APB_MISC_GP_SPI1_CS0_CFGPADCTRL_0,
+            PinGrP::Spi1Cs1Pc4 => &gp.APB_MISC_GP_SPI1_CS1_CFGPADCTRL_0,
+            PinGrP::Spi2MosiPb4 => &gp.APB_MISC_GP_SPI2_MOSI_CFGPADCTRL_0,
+            PinGrP::Spi2MisoPb5 => &gp.APB_MISC_GP_SPI2_MISO_CFGPADCTRL_0,
+            PinGrP::Spi2SckPb6 => &gp.APB_MISC_GP_SPI2_SCK_CFGPADCTRL_0,
+            PinGrP::Spi2Cs0Pb7 => &gp.APB_MISC_GP_SPI2_CS0_CFGPADCTRL_0,
+            PinGrP::Spi2Cs1Pdd0 => &gp.APB_MISC_GP_SPI2_CS1_CFGPADCTRL_0,
+            PinGrP::Spi4MosiPc7 => &gp.APB_MISC_GP_SPI4_MOSI_CFGPADCTRL_0,
+            PinGrP::Spi4MisoPd0 => &gp.APB_MISC_GP_SPI4_MISO_CFGPADCTRL_0,
+            PinGrP::Spi4SckPc5 => &gp.APB_MISC_GP_SPI4_SCK_CFGPADCTRL_0,
+            PinGrP::Spi4Cs0Pc6 => &gp.APB_MISC_GP_SPI4_CS0_CFGPADCTRL_0,
+            PinGrP::QspiSckPee0 => &gp.APB_MISC_GP_QSPI_SCK_CFGPADCTRL_0,
+            PinGrP::Dmic1ClkPe0 => &gp.APB_MISC_GP_DMIC1_CLK_CFGPADCTRL_0,
+            PinGrP::Dmic1DatPe1 => &gp.APB_MISC_GP_DMIC1_DAT_CFGPADCTRL_0,
+            PinGrP::Dmic2ClkPe2 => &gp.APB_MISC_GP_DMIC2_CLK_CFGPADCTRL_0,
+            PinGrP::Dmic2DatPe3 => &gp.APB_MISC_GP_DMIC2_DAT_CFGPADCTRL_0,
+            PinGrP::Dmic3ClkPe4 => &gp.APB_MISC_GP_DMIC3_CLK_CFGPADCTRL_0,
+            PinGrP::Dmic3DatPe5 => &gp.APB_MISC_GP_DMIC3_DAT_CFGPADCTRL_0,
+            PinGrP::Gen1I2CSclPj1 => &gp.APB_MISC_GP_GEN1_I2C_SCL_CFGPADCTRL_0,
+            PinGrP::Gen1I2CSdaPj0 => &gp.APB_MISC_GP_GEN1_I2C_SDA_CFGPADCTRL_0,
+            PinGrP::Gen2I2CSclPj2 => &gp.APB_MISC_GP_GEN2_I2C_SCL_CFGPADCTRL_0,
+            PinGrP::Gen2I2CSdaPj3 => &gp.APB_MISC_GP_GEN2_I2C_SDA_CFGPADCTRL_0,
+            PinGrP::Gen3I2CSclPf0 => &gp.APB_MISC_GP_GEN3_I2C_SCL_CFGPADCTRL_0,
+            PinGrP::Gen3I2CSdaPf1 => &gp.APB_MISC_GP_GEN3_I2C_SDA_CFGPADCTRL_0,
+            PinGrP::CamI2CSclPs2 => &gp.APB_MISC_GP_CAM_I2C_SCL_CFGPADCTRL_0,
+            PinGrP::CamI2CSdaPs3 => &gp.APB_MISC_GP_CAM_I2C_SDA_CFGPADCTRL_0,
+            PinGrP::PwrI2CSclPy3 => &gp.APB_MISC_GP_PWR_I2C_SCL_CFGPADCTRL_0,
+            PinGrP::PwrI2CSdaPy4 => &gp.APB_MISC_GP_PWR_I2C_SDA_CFGPADCTRL_0,
+            PinGrP::Uart1TxPu0 => &gp.APB_MISC_GP_UART1_TX_CFGPADCTRL_0,
+            PinGrP::Uart1RxPu1 => &gp.APB_MISC_GP_UART1_RX_CFGPADCTRL_0,
+            PinGrP::Uart1RtsPu2 => &gp.APB_MISC_GP_UART1_RTS_CFGPADCTRL_0,
+            PinGrP::Uart1CtsPu3 => &gp.APB_MISC_GP_UART1_CTS_CFGPADCTRL_0,
+            PinGrP::Uart2TxPg0 => &gp.APB_MISC_GP_UART2_TX_CFGPADCTRL_0,
+            PinGrP::Uart2RxPg1 => &gp.APB_MISC_GP_UART2_RX_CFGPADCTRL_0,
+            PinGrP::Uart2RtsPg2 => &gp.APB_MISC_GP_UART2_RTS_CFGPADCTRL_0,
+            PinGrP::Uart2CtsPg3 => &gp.APB_MISC_GP_UART2_CTS_CFGPADCTRL_0,
+            PinGrP::Uart3TxPd1 => &gp.APB_MISC_GP_UART3_TX_CFGPADCTRL_0,
+            PinGrP::Uart3RxPd2 => &gp.APB_MISC_GP_UART3_RX_CFGPADCTRL_0,
+            PinGrP::Uart3RtsPd3 => &gp.APB_MISC_GP_UART3_RTS_CFGPADCTRL_0,
+            PinGrP::Uart3CtsPd4 => &gp.APB_MISC_GP_UART3_CTS_CFGPADCTRL_0,
+            PinGrP::Uart4TxPi4 => &gp.APB_MISC_GP_UART4_TX_CFGPADCTRL_0,
+            PinGrP::Uart4RxPi5 => &gp.APB_MISC_GP_UART4_RX_CFGPADCTRL_0,
+            PinGrP::Uart4RtsPi6 => &gp.APB_MISC_GP_UART4_RTS_CFGPADCTRL_0,
+            PinGrP::Uart4CtsPi7 => &gp.APB_MISC_GP_UART4_CTS_CFGPADCTRL_0,
+            PinGrP::Dap1FsPb0 => &gp.APB_MISC_GP_DAP1_FS_CFGPADCTRL_0,
+            PinGrP::Dap1DinPb1 => &gp.APB_MISC_GP_DAP1_DIN_CFGPADCTRL_0,
+            PinGrP::Dap1DoutPb2 => &gp.APB_MISC_GP_DAP1_DOUT_CFGPADCTRL_0,
+            PinGrP::Dap1SclkPb3 => &gp.APB_MISC_GP_DAP1_SCLK_CFGPADCTRL_0,
+            PinGrP::Dap2FsPaa0 => &gp.APB_MISC_GP_DAP2_FS_CFGPADCTRL_0,
+            PinGrP::Dap2DinPaa2 => &gp.APB_MISC_GP_DAP2_DIN_CFGPADCTRL_0,
+            PinGrP::Dap2DoutPaa3 => &gp.APB_MISC_GP_DAP2_DOUT_CFGPADCTRL_0,
+            PinGrP::Dap2SclkPaa1 => &gp.APB_MISC_GP_DAP2_SCLK_CFGPADCTRL_0,
+            PinGrP::Dap4FsPj4 => &gp.APB_MISC_GP_DAP4_FS_CFGPADCTRL_0,
+            PinGrP::Dap4DinPj5 => &gp.APB_MISC_GP_DAP4_DIN_CFGPADCTRL_0,
+            PinGrP::Dap4DoutPj6 => &gp.APB_MISC_GP_DAP4_DOUT_CFGPADCTRL_0,
+            PinGrP::Dap4SclkPj7 => &gp.APB_MISC_GP_DAP4_SCLK_CFGPADCTRL_0,
+            PinGrP::Cam1MclkPs0 => &gp.APB_MISC_GP_CAM1_MCLK_CFGPADCTRL_0,
+            PinGrP::Cam2MclkPs1 => &gp.APB_MISC_GP_CAM2_MCLK_CFGPADCTRL_0,
+            PinGrP::JtagRtck => &gp.APB_MISC_GP_JTAG_RTCK_CFGPADCTRL_0,
+            PinGrP::Clk32KIn => &gp.APB_MISC_GP_CLK_32K_IN_CFGPADCTRL_0,
+            PinGrP::Clk32KOutPy5 => &gp.APB_MISC_GP_CLK_32K_OUT_CFGPADCTRL_0,
+            PinGrP::BattBcl => &gp.APB_MISC_GP_BATT_BCL_CFGPADCTRL_0,
+            PinGrP::ClkReq => &gp.APB_MISC_GP_CLK_REQ_CFGPADCTRL_0,
+            PinGrP::CpuPwrReq => &gp.APB_MISC_GP_CPU_PWR_REQ_CFGPADCTRL_0,
+            PinGrP::PwrIntN => &gp.APB_MISC_GP_PWR_INT_N_CFGPADCTRL_0,
+            PinGrP::Shutdown => &gp.APB_MISC_GP_SHUTDOWN_CFGPADCTRL_0,
+            PinGrP::CorePwrReq => &gp.APB_MISC_GP_CORE_PWR_REQ_CFGPADCTRL_0,
+            PinGrP::AudMclkPbb0 => &gp.APB_MISC_GP_AUD_MCLK_CFGPADCTRL_0,
+            PinGrP::DvfsPwmPbb1 => &gp.APB_MISC_GP_DVFS_PWM_CFGPADCTRL_0,
+            PinGrP::DvfsClkPbb2 => &gp.APB_MISC_GP_DVFS_CLK_CFGPADCTRL_0,
+            PinGrP::GpioX1AudPbb3 => &gp.APB_MISC_GP_GPIO_X1_AUD_CFGPADCTRL_0,
+            PinGrP::GpioX3AudPbb4 => &gp.APB_MISC_GP_GPIO_X3_AUD_CFGPADCTRL_0,
+            PinGrP::Pcc7 => &gp.APB_MISC_GP_GPIO_PCC7_CFGPADCTRL_0,
+            PinGrP::HdmiCecPcc0 => &gp.APB_MISC_GP_HDMI_CEC_CFGPADCTRL_0,
+            PinGrP::HdmiIntDpHpdPcc1 => &gp.APB_MISC_GP_HDMI_INT_DP_HPD_CFGPADCTRL_0,
+            PinGrP::SpdifOutPcc2 => &gp.APB_MISC_GP_SPDIF_OUT_CFGPADCTRL_0,
+            PinGrP::SpdifInPcc3 => &gp.APB_MISC_GP_SPDIF_IN_CFGPADCTRL_0,
+            PinGrP::UsbVbusEn0Pcc4 => &gp.APB_MISC_GP_USB_VBUS_EN0_CFGPADCTRL_0,
+            PinGrP::UsbVbusEn1Pcc5 => &gp.APB_MISC_GP_USB_VBUS_EN1_CFGPADCTRL_0,
+            PinGrP::DpHpd0Pcc6 => &gp.APB_MISC_GP_DP_HPD_CFGPADCTRL_0,
+            PinGrP::WifiEnPh0 => &gp.APB_MISC_GP_WIFI_EN_CFGPADCTRL_0,
+            PinGrP::WifiRstPh1 => &gp.APB_MISC_GP_WIFI_RST_CFGPADCTRL_0,
+            PinGrP::WifiWakeApPh2 => &gp.APB_MISC_GP_WIFI_WAKE_AP_CFGPADCTRL_0,
+            PinGrP::ApWakeBtPh3 => &gp.APB_MISC_GP_AP_WAKE_BT_CFGPADCTRL_0,
+            PinGrP::BtRstPh4 => &gp.APB_MISC_GP_BT_RST_CFGPADCTRL_0,
+            PinGrP::BtWakeApPh5 => &gp.APB_MISC_GP_BT_WAKE_AP_CFGPADCTRL_0,
+            PinGrP::ApWakeNfcPh7 => &gp.APB_MISC_GP_AP_WAKE_NFC_CFGPADCTRL_0,
+            PinGrP::NfcEnPi0 => &gp.APB_MISC_GP_NFC_EN_CFGPADCTRL_0,
+            PinGrP::NfcIntPi1 => &gp.APB_MISC_GP_NFC_INT_CFGPADCTRL_0,
+            PinGrP::GpsEnPi2 => &gp.APB_MISC_GP_GPS_EN_CFGPADCTRL_0,
+            PinGrP::GpsRstPi3 => &gp.APB_MISC_GP_GPS_RST_CFGPADCTRL_0,
+            PinGrP::CamRstPs4 => &gp.APB_MISC_GP_CAM_RST_CFGPADCTRL_0,
+            PinGrP::CamAfEnPs5 => &gp.APB_MISC_GP_CAM_AF_EN_CFGPADCTRL_0,
+            PinGrP::CamFlashEnPs6 => &gp.APB_MISC_GP_CAM_FLASH_EN_CFGPADCTRL_0,
+            PinGrP::Cam1PwdnPs7 => &gp.APB_MISC_GP_CAM1_PWDN_CFGPADCTRL_0,
+            PinGrP::Cam2PwdnPt0 => &gp.APB_MISC_GP_CAM2_PWDN_CFGPADCTRL_0,
+            PinGrP::Cam1StrobePt1 => &gp.APB_MISC_GP_CAM1_STROBE_CFGPADCTRL_0,
+            PinGrP::LcdTePy2 => &gp.APB_MISC_GP_LCD_TE_CFGPADCTRL_0,
+            PinGrP::LcdBlPwmPv0 => &gp.APB_MISC_GP_LCD_BL_PWM_CFGPADCTRL_0,
+            PinGrP::LcdBlEnPv1 => &gp.APB_MISC_GP_LCD_BL_EN_CFGPADCTRL_0,
+            PinGrP::LcdRstPv2 => &gp.APB_MISC_GP_LCD_RST_CFGPADCTRL_0,
+            PinGrP::LcdGpio1Pv3 => &gp.APB_MISC_GP_LCD_GPIO1_CFGPADCTRL_0,
+            PinGrP::LcdGpio2Pv4 => &gp.APB_MISC_GP_LCD_GPIO2_CFGPADCTRL_0,
+            PinGrP::ApReadyPv5 => &gp.APB_MISC_GP_AP_READY_CFGPADCTRL_0,
+            PinGrP::TouchRstPv6 => &gp.APB_MISC_GP_TOUCH_RST_CFGPADCTRL_0,
+            PinGrP::TouchClkPv7 => &gp.APB_MISC_GP_TOUCH_CLK_CFGPADCTRL_0,
+            PinGrP::ModemWakeApPx0 => &gp.APB_MISC_GP_MODEM_WAKE_AP_CFGPADCTRL_0,
+            PinGrP::TouchIntPx1 => &gp.APB_MISC_GP_TOUCH_INT_CFGPADCTRL_0,
+            PinGrP::MotionIntPx2 => &gp.APB_MISC_GP_MOTION_INT_CFGPADCTRL_0,
+            PinGrP::AlsProxIntPx3 => &gp.APB_MISC_GP_ALS_PROX_INT_CFGPADCTRL_0,
+            PinGrP::TempAlertPx4 => &gp.APB_MISC_GP_TEMP_ALERT_CFGPADCTRL_0,
+            PinGrP::ButtonPowerOnPx5 => &gp.APB_MISC_GP_BUTTON_POWER_ON_CFGPADCTRL_0,
+            PinGrP::ButtonVolUpPx6 => &gp.APB_MISC_GP_BUTTON_VOL_UP_CFGPADCTRL_0,
+            PinGrP::ButtonVolDownPx7 => &gp.APB_MISC_GP_BUTTON_VOL_DOWN_CFGPADCTRL_0,
+            PinGrP::ButtonSlideSwPy0 => &gp.APB_MISC_GP_BUTTON_SLIDE_SW_CFGPADCTRL_0,
+            PinGrP::ButtonHomePy1 => &gp.APB_MISC_GP_BUTTON_HOME_CFGPADCTRL_0,
+            PinGrP::Pa6 => &gp.APB_MISC_GP_GPIO_PA6_CFGPADCTRL_0,
+            PinGrP::Pe6 => &gp.APB_MISC_GP_GPIO_PE6_CFGPADCTRL_0,
+            PinGrP::Pe7 => &gp.APB_MISC_GP_GPIO_PE7_CFGPADCTRL_0,
+            PinGrP::Ph6 => &gp.APB_MISC_GP_GPIO_PH6_CFGPADCTRL_0,
+            PinGrP::Pk0 => &gp.APB_MISC_GP_GPIO_PK0_CFGPADCTRL_0,
+            PinGrP::Pk1 => &gp.APB_MISC_GP_GPIO_PK1_CFGPADCTRL_0,
+            PinGrP::Pk2 => &gp.APB_MISC_GP_GPIO_PK2_CFGPADCTRL_0,
+            PinGrP::Pk3 => &gp.APB_MISC_GP_GPIO_PK3_CFGPADCTRL_0,
+            PinGrP::Pk4 => &gp.APB_MISC_GP_GPIO_PK4_CFGPADCTRL_0,
+            PinGrP::Pk5 => &gp.APB_MISC_GP_GPIO_PK5_CFGPADCTRL_0,
+            PinGrP::Pk6 => &gp.APB_MISC_GP_GPIO_PK6_CFGPADCTRL_0,
+            PinGrP::Pk7 => &gp.APB_MISC_GP_GPIO_PK7_CFGPADCTRL_0,
+            PinGrP::Pl0 => &gp.APB_MISC_GP_GPIO_PL0_CFGPADCTRL_0,
+            PinGrP::Pl1 => &gp.APB_MISC_GP_GPIO_PL1_CFGPADCTRL_0,
+            PinGrP::Pz0 => &gp.APB_MISC_GP_GPIO_PZ0_CFGPADCTRL_0,
+            PinGrP::Pz1 => &gp.APB_MISC_GP_GPIO_PZ1_CFGPADCTRL_0,
+            PinGrP::Pz2 => &gp.APB_MISC_GP_GPIO_PZ2_CFGPADCTRL_0,
+            PinGrP::Pz3 => &gp.APB_MISC_GP_GPIO_PZ3_CFGPADCTRL_0,
+            PinGrP::Pz4 => &gp.APB_MISC_GP_GPIO_PZ4_CFGPADCTRL_0,
+            PinGrP::Pz5 => &gp.APB_MISC_GP_GPIO_PZ5_CFGPADCTRL_0,
+            _ => return None,
+        };
+
+        Some(unsafe { &*(register as *const ReadWrite<u32> as *const _) })
+    }
+
+    /// Configures the pull-up and pull-down drive strengths of this Pin Group.
+    ///
+    /// Both values range from 0 to 31, where higher values drive the pad harder.
+    /// This helps with signal integrity on long or heavily loaded lines, e.g.
+    /// when running an SPI bus at high frequencies.
+    ///
+    /// NOTE: Pads that are part of the SDMMC and QSPI pad groups are configured
+    /// through their group registers and are not supported by this method.
+    pub fn set_drive_strength(self, pull_up: u8, pull_down: u8) -> Result<(), PadControlError> {
+        let register = self.pad_control().ok_or(PadControlError::Unsupported)?;
+
+        if pull_up > 31 || pull_down > 31 {
+            return Err(PadControlError::InvalidValue);
+        }
+
+        register.modify(
+            APB_MISC_GP_CFGPADCTRL_0::CFG2TMC_DRVUP.val(pull_up as u32)
+                + APB_MISC_GP_CFGPADCTRL_0::CFG2TMC_DRVDN.val(pull_down as u32),
+        );
+
+        Ok(())
+    }
+
+    /// Configures the rising and falling edge slew rates of this Pin Group.
+    ///
+    /// Both values range from 0 to 3, where lower values produce faster edges.
+    ///
+    /// NOTE: Pads that are part of the SDMMC and QSPI pad groups are configured
+    /// through their group registers and are not supported by this method.
+    pub fn set_slew_rate(self, rising: u8, falling: u8) -> Result<(), PadControlError> {
+        let register = self.pad_control().ok_or(PadControlError::Unsupported)?;
+
+        if rising > 3 || falling > 3 {
+            return Err(PadControlError::InvalidValue);
+        }
+
+        register.modify(
+            APB_MISC_GP_CFGPADCTRL_0::CFG2TMC_SLWR.val(rising as u32)
+                + APB_MISC_GP_CFGPADCTRL_0::CFG2TMC_SLWF.val(falling as u32),
+        );
+
+        Ok(())
+    }
+
     /// Configures the given operation voltage state for this Pin Group.
     pub fn set_e_io_hv(self, hv: PinEIoHv) {
         if hv == PinEIoHv::Default {
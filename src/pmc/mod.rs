@@ -54,12 +54,15 @@
 //! [`Partition`]: enum.Partition.html
 //! [`powergate_partition`]: fn.powergate_partition.html
 
-use crate::timer::usleep;
+use crate::timer::wait_until;
 
 pub use registers::*;
 
 mod registers;
 
+/// The time in microseconds to wait for the power gating controller.
+const POWERGATE_TIMEOUT_US: u32 = 5000;
+
 enum_from_primitive! {
     /// Enumeration over power-gated PMC partitions.
     #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -114,29 +117,19 @@ pub fn powergate_partition(partition: Partition, enable: bool) -> Result<(), ()>
     }
 
     // Wait for the power gating controller to enter idle state.
-    let mut i = 5001;
-    while (pmc.APBDEV_PMC_PWRGATE_TOGGLE_0.get() & 0x100) != 0 {
-        usleep(1);
-        i -= 1;
-
-        if i < 1 {
-            return Err(());
-        }
-    }
+    wait_until(
+        || (pmc.APBDEV_PMC_PWRGATE_TOGGLE_0.get() & 0x100) == 0,
+        POWERGATE_TIMEOUT_US,
+    )
+    .map_err(|_| ())?;
 
     // Toggle power gating.
     pmc.APBDEV_PMC_PWRGATE_TOGGLE_0.set(partition_id | 0x100);
 
     // Wait for the changes to take effect.
-    i = 5001;
-    while i > 0 {
-        if (pmc.APBDEV_PMC_PWRGATE_STATUS_0.get() & partition_mask) == desired_state {
-            return Ok(());
-        }
-
-        usleep(1);
-        i -= 1;
-    }
-
-    Err(())
+    wait_until(
+        || (pmc.APBDEV_PMC_PWRGATE_STATUS_0.get() & partition_mask) == desired_state,
+        POWERGATE_TIMEOUT_US,
+    )
+    .map_err(|_| ())
 }
@@ -3,7 +3,7 @@
 //! See Chapter 12.6 in the Tegra X1 Technical Reference Manual
 //! for details.
 
-use register::mmio::*;
+use register::{mmio::*, register_bitfields};
 
 use crate::memory_map::PMC;
 
@@ -12,6 +12,29 @@ pub const REGISTERS: *const Registers = PMC as *const Registers;
 
 // TODO: Bitfields.
 
+register_bitfields! {
+    u32,
+
+    /// Bitfields of the `APBDEV_PMC_RST_STATUS_0` register.
+    pub APBDEV_PMC_RST_STATUS_0 [
+        /// The source of the last reset of the system.
+        RST_SOURCE OFFSET(0) NUMBITS(3) [
+            /// A power-on reset, i.e. a cold boot.
+            PowerOnReset = 0,
+            /// A reset by any of the watchdogs.
+            Watchdog = 1,
+            /// A reset by the thermal sensor.
+            Sensor = 2,
+            /// A software reset through `APBDEV_PMC_CNTRL_0`.
+            SwMain = 3,
+            /// A wake from the LP0 state.
+            Lp0 = 4,
+            /// A reset by the always-on thermal alert.
+            Aotag = 5
+        ]
+    ]
+}
+
 // TODO: Convert this struct to register_structs! format.
 
 /// Representation of the PMC registers.
@@ -127,7 +150,7 @@ pub struct Registers {
     pub APBDEV_PMC_CLK_OUT_CNTRL_0: ReadWrite<u32>,
     pub APBDEV_PMC_SATA_PWRGT_0: ReadWrite<u32>,
     pub APBDEV_PMC_SENSOR_CTRL_0: ReadWrite<u32>,
-    pub APBDEV_PMC_RST_STATUS_0: ReadWrite<u32>,
+    pub APBDEV_PMC_RST_STATUS_0: ReadWrite<u32, APBDEV_PMC_RST_STATUS_0::Register>,
     pub APBDEV_PMC_IO_DPD_REQ_0: ReadWrite<u32>,
     pub APBDEV_PMC_IO_DPD_STATUS_0: ReadWrite<u32>,
     pub APBDEV_PMC_IO_DPD2_REQ_0: ReadWrite<u32>,
@@ -0,0 +1,224 @@
+//! A randomized test harness for the SPI driver on top of the [`mock`] backend.
+//!
+//! Random sequences of configuration changes and transfers are run against
+//! emulated controllers. Invalid arguments and unreachable configurations
+//! are expected to be rejected with errors, but the driver must never panic
+//! and must always leave the controller idle and consistently configured.
+//!
+//! The harness is only built with the `mock` feature, i.e. it is run through
+//! `cargo test --features mock`.
+//!
+//! [`mock`]: ../mock/index.html
+
+use core::{convert::TryFrom, mem::MaybeUninit, sync::atomic::Ordering};
+
+use super::{mock::MockSpi, *};
+use crate::timer::get_microseconds;
+
+/// The amount of random sequences to run.
+const SEQUENCES: u32 = 32;
+
+/// The amount of operations per sequence.
+const OPERATIONS: usize = 256;
+
+/// The length of the largest transfer, in bytes.
+const MAX_LENGTH: usize = 4 * FIFO_DEPTH * 4 + 1;
+
+/// A xorshift generator, which is plenty for picking operations.
+struct Rng(u32);
+
+impl Rng {
+    /// Gets the next random value.
+    fn next_u32(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+
+        self.0
+    }
+
+    /// Gets a random value in the range `0..bound`.
+    fn below(&mut self, bound: u32) -> u32 {
+        self.next_u32() % bound
+    }
+
+    /// Gets a random boolean.
+    fn flip(&mut self) -> bool {
+        self.next_u32() & 1 != 0
+    }
+
+    /// Picks a random element of a slice.
+    fn pick<T: Copy>(&mut self, values: &[T]) -> T {
+        values[self.below(values.len() as u32) as usize]
+    }
+
+    /// Gets a random transfer length.
+    ///
+    /// Empty and single-byte transfers as well as transfers that exceed the
+    /// FIFOs and the exchange buffer are picked more often than the rest.
+    fn length(&mut self) -> usize {
+        match self.below(6) {
+            0 => 0,
+            1 => 1,
+            2 => EXCHANGE_BUFFER_SIZE + 1,
+            3 => FIFO_DEPTH * 4 + 1,
+            4 => MAX_LENGTH,
+            _ => self.below(4 * PACKED_THRESHOLD as u32) as usize,
+        }
+    }
+}
+
+/// Waits for a non-blocking transfer to complete.
+fn complete(spi: &Spi) -> Result<(), SpiError> {
+    loop {
+        match spi.poll_complete() {
+            Err(nb::Error::WouldBlock) => {
+                // Let the emulated controller make progress.
+                get_microseconds();
+            }
+            Err(nb::Error::Other(error)) => return Err(error),
+            Ok(()) => return Ok(()),
+        }
+    }
+}
+
+/// Checks that the controller is idle and consistently configured.
+fn check_idle(mock: &MockSpi) {
+    let spi = mock.spi();
+    let command = &mock.registers().SPI_COMMAND_0;
+
+    // No transfer is in flight.
+    assert!(!spi.is_busy());
+    assert_eq!(spi.ensure_idle(), Ok(()));
+    assert!(mock.registers().SPI_TRANSFER_STATUS_0.is_set(SPI_TRANSFER_STATUS_0::RDY));
+    assert!(!spi.state().exchanging.load(Ordering::Relaxed));
+
+    // The per-transfer bits have been cleared.
+    assert!(!command.is_set(SPI_COMMAND_0::TX_EN));
+    assert!(!command.is_set(SPI_COMMAND_0::RX_EN));
+    assert!(!command.is_set(SPI_COMMAND_0::BIDIR));
+    assert!(!mock.registers().SPI_INTR_MASK_0.is_set(SPI_INTR_MASK_0::RDY_INTR_MASK));
+
+    // The configuration is one that can be set.
+    assert!((1..=32).contains(&spi.word_length()));
+    assert!((1..=4).contains(&spi.bytes_per_packet()));
+}
+
+/// Runs a single random operation against the controller.
+fn run_operation(rng: &mut Rng, mock: &MockSpi, tx: &[u8], rx: &mut [u8]) {
+    let spi = mock.spi();
+    let length = rng.length();
+    let (tx, rx) = (&tx[..length], &mut rx[..length]);
+
+    match rng.below(20) {
+        0 => {
+            let bits = rng.below(40) as u8;
+            let result = spi.set_word_length(bits);
+
+            assert_eq!(result.is_ok(), (1..=32).contains(&bits));
+        }
+        1 => {
+            let mode = rng.pick(&[SpiMode::Mode0, SpiMode::Mode1, SpiMode::Mode2, SpiMode::Mode3]);
+            spi.set_mode(mode).unwrap();
+        }
+        2 => {
+            let order = rng.pick(&[BitOrder::MsbFirst, BitOrder::LsbFirst]);
+            spi.set_bit_order(order).unwrap();
+        }
+        3 => {
+            let cs = ChipSelect::try_from(rng.below(4) as u8).unwrap();
+            spi.set_chip_select(cs).unwrap();
+            spi.set_cs_polarity(cs, rng.flip()).unwrap();
+        }
+        4 => spi.set_timeout(rng.pick(&[0, 1, 100, DEFAULT_TIMEOUT_US])),
+        5 => {
+            let timing = CsTiming {
+                setup_cycles: rng.below(0x14) as u8,
+                hold_cycles: rng.below(0x14) as u8,
+                inactive_cycles: rng.below(0x24) as u8,
+            };
+
+            let _ = spi.set_cs_timing(timing);
+        }
+        6 => {
+            mock.set_clock_rate(rng.pick(&[0, 1_000_000, DEFAULT_FREQUENCY, MAX_FREQUENCY]));
+            spi.set_min_cs_inactive_us(rng.below(100)).unwrap();
+        }
+        7 => spi.set_manual_cs(rng.flip()),
+        8 => {
+            let _ = spi.send(tx);
+        }
+        9 => {
+            let _ = spi.receive(rx);
+        }
+        10 => {
+            let _ = spi.transfer(tx, rx);
+        }
+        11 => {
+            let split = rng.below(length as u32 + 1) as usize;
+            let _ = spi.write_then_read(&tx[..split], &mut rx[split..]);
+        }
+        12 => {
+            let split = rng.below(length as u32 + 1) as usize;
+            let _ = spi.transfer_half_duplex(&tx[..split], &mut rx[split..]);
+        }
+        13 => {
+            if let Ok(response) = spi.exchange(tx) {
+                assert_eq!(response.len(), length);
+            }
+        }
+        14 => {
+            let words = [0x1234_5678; 64];
+            let mut received = [0; 64];
+            let count = length.min(words.len());
+
+            let _ = spi.transfer_words(&words[..count], &mut received[..count]);
+        }
+        15 => {
+            if spi.start_send(tx).is_ok() {
+                complete(&spi).unwrap();
+            }
+        }
+        16 => {
+            if spi.try_send_byte(rng.next_u32() as u8).is_ok() {
+                complete(&spi).unwrap();
+            }
+        }
+        17 => {
+            let mut buf = [MaybeUninit::uninit(); MAX_LENGTH];
+            if let Ok(received) = spi.read_uninit(&mut buf[..length]) {
+                assert_eq!(received.len(), length);
+            }
+        }
+        18 => {
+            let state = spi.save_state();
+            spi.restore_state(&state).unwrap();
+        }
+        _ => {
+            let _ = spi.flush_fifos();
+        }
+    }
+}
+
+#[test]
+fn random_operations_keep_the_controller_valid() {
+    let mut tx = [0; MAX_LENGTH];
+    let mut rx = [0; MAX_LENGTH];
+
+    for seed in 1..=SEQUENCES {
+        let mut rng = Rng(seed.wrapping_mul(0x9E37_79B9));
+        let mock = MockSpi::new();
+        check_idle(&mock);
+
+        for byte in tx.iter_mut() {
+            *byte = rng.next_u32() as u8;
+        }
+
+        for _ in 0..OPERATIONS {
+            mock.set_response(if rng.flip() { Some(rng.next_u32()) } else { None });
+
+            run_operation(&mut rng, &mock, &tx, &mut rx);
+            check_idle(&mock);
+        }
+    }
+}
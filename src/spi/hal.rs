@@ -0,0 +1,54 @@
+//! Implementations of the [`embedded-hal`] SPI traits.
+//!
+//! [`embedded-hal`]: https://docs.rs/embedded-hal/1.0.0
+
+use embedded_hal::spi::{self, ErrorKind, ErrorType, SpiBus};
+
+use super::{as_uninit, Spi, SpiError, FIFO_DEPTH, PACKED_THRESHOLD};
+
+impl spi::Error for SpiError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            SpiError::TxOverflow | SpiError::RxOverflow => ErrorKind::Overrun,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+impl ErrorType for Spi {
+    type Error = SpiError;
+}
+
+impl SpiBus<u8> for Spi {
+    fn read(&mut self, words: &mut [u8]) -> Result<(), SpiError> {
+        self.pio_receive_packet(words)
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), SpiError> {
+        self.pio_send_packet(words)
+    }
+
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), SpiError> {
+        // The shorter buffer is padded by the PIO transfer flow.
+        self.pio_transfer(Some(write), Some(as_uninit(read)), PACKED_THRESHOLD)
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), SpiError> {
+        let mut buffer = [0; FIFO_DEPTH * 4];
+
+        // Save each chunk before it is overwritten by the received data.
+        for chunk in words.chunks_mut(buffer.len()) {
+            let tx = &mut buffer[..chunk.len()];
+            tx.copy_from_slice(chunk);
+
+            Spi::transfer(self, tx, chunk)?;
+        }
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), SpiError> {
+        // Wait for the controller to become idle and flush the FIFOs.
+        self.flush_fifos()
+    }
+}
@@ -0,0 +1,214 @@
+//! An emulated SPI controller for testing the SPI driver on the host.
+//!
+//! Instead of the memory-mapped registers of a controller, a [`MockSpi`]
+//! owns a zeroed [`Registers`] block in host memory and hands out an [`Spi`]
+//! that operates on it. Tests can preset register values to emulate the
+//! hardware and inspect the values that were written by the driver.
+//!
+//! Every mock carries its own driver state, such as the transfer timeout or
+//! the clock rate, so mocks never share state with each other or with the
+//! controllers 1 through 4, and tests can run in parallel.
+//!
+//! The mock reacts to the driver on every read of the software microsecond
+//! counter of [`timer::mock`], i.e. whenever the driver polls or sleeps:
+//!
+//! - FIFO flush requests complete at once.
+//! - A PIO transaction completes at once. The `PIO` bit is cleared, the
+//!   `RDY` bit is set and, if reception is enabled, the response word set
+//!   through [`MockSpi::set_response`] is shifted into RX FIFO. Without a
+//!   response word, the last word of TX FIFO is looped back instead, or `0`
+//!   if transmission is disabled.
+//!
+//! NOTE: The FIFOs are emulated as single registers, so every word of a
+//! block receives the same response word. The CAR and the DMA engine aren't
+//! emulated, so methods that configure the clock or use DMA must not be
+//! called on a mock.
+//!
+//! [`MockSpi`]: struct.MockSpi.html
+//! [`Registers`]: ../struct.Registers.html
+//! [`Spi`]: ../struct.Spi.html
+//! [`timer::mock`]: ../../timer/mock/index.html
+//! [`MockSpi::set_response`]: struct.MockSpi.html#method.set_response
+
+use core::sync::atomic::Ordering;
+use std::{
+    boxed::Box,
+    cell::{Cell, RefCell},
+    mem,
+    vec::Vec,
+};
+
+use super::{
+    ControllerState, Registers, Spi, SPI_COMMAND_0, SPI_FIFO_STATUS_0, SPI_TRANSFER_STATUS_0,
+};
+use crate::timer::mock as timer;
+
+thread_local! {
+    /// The mocks that are alive on the current thread.
+    static CONTROLLERS: RefCell<Vec<*const MockController>> = RefCell::new(Vec::new());
+}
+
+/// An emulated controller in host memory.
+///
+/// The registers come first, so that a pointer to the controller
+/// is a valid pointer to its registers as well.
+#[repr(C)]
+struct MockController {
+    /// The emulated registers of the controller.
+    registers: Registers,
+    /// The driver state of the controller.
+    state: ControllerState,
+    /// The word that is received for every FIFO word, or `None` for loopback.
+    response: Cell<Option<u32>>,
+    /// The values of `SPI_COMMAND_0` observed by the controller, in order.
+    command_trace: RefCell<Vec<u32>>,
+}
+
+impl MockController {
+    /// Reacts to the current register values, like the hardware would.
+    fn step(&self) {
+        let registers = &self.registers;
+
+        // Note changes of the command register.
+        let command = registers.SPI_COMMAND_0.get();
+        let mut trace = self.command_trace.borrow_mut();
+        if trace.last() != Some(&command) {
+            trace.push(command);
+        }
+
+        // Complete flush requests.
+        registers.SPI_FIFO_STATUS_0.modify(
+            SPI_FIFO_STATUS_0::RX_FIFO_FLUSH::CLEAR + SPI_FIFO_STATUS_0::TX_FIFO_FLUSH::CLEAR
+        );
+
+        // Complete a started transaction.
+        if registers.SPI_COMMAND_0.is_set(SPI_COMMAND_0::PIO) {
+            if registers.SPI_COMMAND_0.is_set(SPI_COMMAND_0::RX_EN) {
+                let word = self.response.get().unwrap_or_else(|| {
+                    if registers.SPI_COMMAND_0.is_set(SPI_COMMAND_0::TX_EN) {
+                        registers.SPI_TX_FIFO_0.get()
+                    } else {
+                        0
+                    }
+                });
+
+                registers.SPI_RX_FIFO_0.set(word);
+            }
+
+            registers.SPI_COMMAND_0.modify(SPI_COMMAND_0::PIO::Stop);
+            registers.SPI_TRANSFER_STATUS_0.modify(SPI_TRANSFER_STATUS_0::RDY::SET);
+        }
+    }
+}
+
+/// Steps all mocks of the current thread.
+fn tick() {
+    CONTROLLERS.with(|controllers| {
+        for &controller in controllers.borrow().iter() {
+            unsafe { &*controller }.step();
+        }
+    });
+}
+
+/// An emulated SPI controller in host memory.
+///
+/// NOTE: A mock reacts to the driver on the thread it was created on only,
+/// so it must not be moved to another thread.
+pub struct MockSpi {
+    /// The emulated controller.
+    controller: Box<MockController>,
+}
+
+impl MockSpi {
+    /// Creates a new mock of an idle controller with all other registers cleared.
+    pub fn new() -> Self {
+        let controller = Box::new(MockController {
+            // SAFETY: The registers are plain `u32` cells, so all zeroes is a valid value.
+            registers: unsafe { mem::zeroed() },
+            state: ControllerState::new(),
+            response: Cell::new(None),
+            command_trace: RefCell::new(Vec::new()),
+        });
+
+        // Signal that no transaction is in flight.
+        controller
+            .registers
+            .SPI_TRANSFER_STATUS_0
+            .modify(SPI_TRANSFER_STATUS_0::RDY::SET);
+
+        CONTROLLERS.with(|controllers| controllers.borrow_mut().push(&*controller));
+        timer::set_tick_hook(Some(tick));
+
+        MockSpi { controller }
+    }
+
+    /// Gets an [`Spi`] that operates on the registers of the mock.
+    ///
+    /// [`Spi`]: ../struct.Spi.html
+    pub fn spi(&self) -> Spi {
+        Spi {
+            registers: &self.controller.registers,
+        }
+    }
+
+    /// Gets the registers of the mock.
+    pub fn registers(&self) -> &Registers {
+        &self.controller.registers
+    }
+
+    /// Sets the word to be received for every FIFO word, or `None` to loop back TX FIFO.
+    pub fn set_response(&self, word: Option<u32>) {
+        self.controller.response.set(word);
+    }
+
+    /// Sets the clock rate of the controller in Hz, as if it was set through the CAR.
+    pub fn set_clock_rate(&self, hz: u32) {
+        self.controller.state.clock_rate.store(hz, Ordering::Relaxed);
+    }
+
+    /// Gets the values of `SPI_COMMAND_0` that the controller observed, in order.
+    ///
+    /// A value is recorded whenever it differs from the previous one at the
+    /// time the controller is stepped, so writes in between two reads of the
+    /// microsecond counter are only observed through their combined result.
+    pub fn command_trace(&self) -> Vec<u32> {
+        self.controller.command_trace.borrow().clone()
+    }
+}
+
+impl Default for MockSpi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for MockSpi {
+    fn drop(&mut self) {
+        let controller: *const MockController = &*self.controller;
+
+        CONTROLLERS.with(|controllers| {
+            let mut controllers = controllers.borrow_mut();
+            controllers.retain(|&other| other != controller);
+
+            if controllers.is_empty() {
+                timer::set_tick_hook(None);
+            }
+        });
+    }
+}
+
+/// Gets the driver state of a mock, if the registers belong to one.
+///
+/// NOTE: The state lives as long as the mock, not for `'static`, just like
+/// the registers that an [`Spi`] of the mock points to.
+///
+/// [`Spi`]: ../struct.Spi.html
+pub(super) fn state(registers: *const Registers) -> Option<&'static ControllerState> {
+    // Mocks live in host memory, outside of the range of the controllers.
+    if (registers as usize).wrapping_sub(super::SPI_1 as usize) < 4 * 0x200 {
+        return None;
+    }
+
+    // SAFETY: The registers are the first field of a live `MockController`.
+    Some(unsafe { &(*(registers as *const MockController)).state })
+}
@@ -1,18 +1,577 @@
 //! Driver for the Tegra X1 Serial Peripheral Interface Controller.
 
-use core::convert::TryInto;
+use core::{
+    cell::UnsafeCell,
+    convert::TryFrom,
+    fmt, iter,
+    mem::MaybeUninit,
+    ops::Deref,
+    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+};
 
-use crate::timer::usleep;
+use crate::{
+    apb::dma::Channel,
+    cache::{clean_dcache_range, clean_invalidate_dcache_range, invalidate_dcache_range},
+    car::Clock,
+    memory_map::spi::SPI_1,
+    timer::{
+        detect_clk_m_freq, get_microseconds, usleep, wait_for_field, wait_until, ClkMFreq,
+        TimeoutError,
+    },
+};
 
 pub use registers::*;
 
+#[cfg(all(test, feature = "mock"))]
+mod fuzz;
+#[cfg(feature = "embedded-hal")]
+mod hal;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
 mod registers;
 
+/// Enumeration of potential errors that may occur
+/// during communication over SPI.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum SpiError {
+    /// An error that was indicated through the `SPI_FIFO_STATUS_0` register
+    /// without any of the more specific status bits being set.
+    FifoError,
+    /// TX FIFO was written while it was full.
+    TxOverflow,
+    /// TX FIFO ran empty while data was still to be transmitted.
+    TxUnderrun,
+    /// RX FIFO received data while it was full.
+    RxOverflow,
+    /// RX FIFO was read while it was empty.
+    RxUnderrun,
+    /// A buffer for a DMA transfer is not aligned to a 4-byte boundary.
+    UnalignedBuffer,
+    /// A buffer has a length that is not supported by the transfer mode.
+    InvalidLength,
+    /// A buffer exceeds the maximum length supported by the transfer mode.
+    BufferTooLarge,
+    /// The APB DMA channel could not be configured for the transfer.
+    DmaError,
+    /// An invalid combination of arguments was passed to a method.
+    InvalidArgument,
+    /// The controller was reconfigured while a transfer was in progress.
+    Busy,
+    /// The controller didn't complete a transfer in time.
+    Timeout,
+    /// The data read back during a self-test doesn't match the data that was sent.
+    LoopbackMismatch,
+}
+
+/// The chip-select lines of an SPI controller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChipSelect {
+    /// Chip-select line 0.
+    Cs0,
+    /// Chip-select line 1.
+    Cs1,
+    /// Chip-select line 2.
+    Cs2,
+    /// Chip-select line 3.
+    Cs3,
+}
+
+impl fmt::Display for SpiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SpiError::FifoError => "SPI FIFO error",
+            SpiError::TxOverflow => "SPI TX FIFO overflow",
+            SpiError::TxUnderrun => "SPI TX FIFO underrun",
+            SpiError::RxOverflow => "SPI RX FIFO overflow",
+            SpiError::RxUnderrun => "SPI RX FIFO underrun",
+            SpiError::UnalignedBuffer => "SPI DMA buffer not word-aligned",
+            SpiError::InvalidLength => "SPI buffer length not supported",
+            SpiError::BufferTooLarge => "SPI buffer too large",
+            SpiError::DmaError => "SPI DMA channel setup failed",
+            SpiError::InvalidArgument => "invalid SPI argument",
+            SpiError::Busy => "SPI controller busy",
+            SpiError::Timeout => "SPI transfer timed out",
+            SpiError::LoopbackMismatch => "SPI loopback data mismatch",
+        })
+    }
+}
+
+impl From<TimeoutError> for SpiError {
+    fn from(_: TimeoutError) -> Self {
+        SpiError::Timeout
+    }
+}
+
+impl TryFrom<u8> for ChipSelect {
+    type Error = SpiError;
+
+    /// Converts the number of a chip-select line into a [`ChipSelect`].
+    ///
+    /// Fails with [`SpiError::InvalidArgument`] for numbers other than 0 through 3.
+    ///
+    /// [`ChipSelect`]: enum.ChipSelect.html
+    /// [`SpiError::InvalidArgument`]: enum.SpiError.html#variant.InvalidArgument
+    fn try_from(cs: u8) -> Result<Self, SpiError> {
+        match cs {
+            0 => Ok(ChipSelect::Cs0),
+            1 => Ok(ChipSelect::Cs1),
+            2 => Ok(ChipSelect::Cs2),
+            3 => Ok(ChipSelect::Cs3),
+            _ => Err(SpiError::InvalidArgument),
+        }
+    }
+}
+
+/// The SPI modes, as combinations of clock polarity (CPOL) and clock phase (CPHA).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpiMode {
+    /// The clock idles low and data is sampled on the rising edge.
+    Mode0,
+    /// The clock idles low and data is sampled on the falling edge.
+    Mode1,
+    /// The clock idles high and data is sampled on the falling edge.
+    Mode2,
+    /// The clock idles high and data is sampled on the rising edge.
+    Mode3,
+}
+
+/// The order in which the bits of a word are transferred.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitOrder {
+    /// The most significant bit is transferred first.
+    MsbFirst,
+    /// The least significant bit is transferred first.
+    LsbFirst,
+}
+
+/// Native word types that can be transferred through [`Spi::write_words`] and [`Spi::read_words`].
+///
+/// This is implemented for `u16` and `u32` and can't be implemented outside of this crate.
+///
+/// [`Spi::write_words`]: struct.Spi.html#method.write_words
+/// [`Spi::read_words`]: struct.Spi.html#method.read_words
+pub trait SpiWord: Copy + sealed::Sealed {
+    /// The length of the word in bits.
+    const BITS: u8;
+
+    /// Writes the word into a buffer of `BITS / 8` bytes, in little-endian byte order.
+    fn write_le(self, bytes: &mut [u8]);
+
+    /// Reads the word from a buffer of `BITS / 8` bytes, in little-endian byte order.
+    fn read_le(bytes: &[u8]) -> Self;
+}
+
+impl SpiWord for u16 {
+    const BITS: u8 = 16;
+
+    fn write_le(self, bytes: &mut [u8]) {
+        bytes.copy_from_slice(&self.to_le_bytes());
+    }
+
+    fn read_le(bytes: &[u8]) -> Self {
+        u16::from_le_bytes([bytes[0], bytes[1]])
+    }
+}
+
+impl SpiWord for u32 {
+    const BITS: u8 = 32;
+
+    fn write_le(self, bytes: &mut [u8]) {
+        bytes.copy_from_slice(&self.to_le_bytes());
+    }
+
+    fn read_le(bytes: &[u8]) -> Self {
+        u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for u16 {}
+    impl Sealed for u32 {}
+}
+
+/// The direction of a continuous DMA stream.
+///
+/// Used by [`Spi::start_stream`].
+///
+/// [`Spi::start_stream`]: struct.Spi.html#method.start_stream
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreamDirection {
+    /// The buffers are transmitted over SPI.
+    Transmit,
+    /// The buffers are filled with data received over SPI.
+    Receive,
+}
+
+/// The interrupt sources of an SPI controller.
+///
+/// Enabled through [`Spi::enable_interrupt`] and acknowledged through
+/// [`Spi::clear_interrupt`].
+///
+/// [`Spi::enable_interrupt`]: struct.Spi.html#method.enable_interrupt
+/// [`Spi::clear_interrupt`]: struct.Spi.html#method.clear_interrupt
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpiInterrupt {
+    /// A transfer has completed, i.e. `RDY` of `SPI_TRANSFER_STATUS_0` is set.
+    TransferDone,
+    /// The TX FIFO has room for as many words as its trigger level.
+    TxFifoTrigger,
+    /// The RX FIFO holds at least as many words as its trigger level.
+    RxFifoTrigger,
+    /// The TX FIFO has overflowed.
+    TxOverflow,
+    /// The TX FIFO has underrun.
+    TxUnderrun,
+    /// The RX FIFO has overflowed.
+    RxOverflow,
+    /// The RX FIFO has underrun.
+    RxUnderrun,
+}
+
+/// A snapshot of the configuration of an SPI controller.
+///
+/// Obtained through [`Spi::save_state`] and applied through [`Spi::restore_state`].
+///
+/// [`Spi::save_state`]: struct.Spi.html#method.save_state
+/// [`Spi::restore_state`]: struct.Spi.html#method.restore_state
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpiState {
+    /// The value of `SPI_COMMAND_0`, without the transfer control bits.
+    command: u32,
+    /// The value of `SPI_COMMAND2_0`.
+    command2: u32,
+    /// The value of `SPI_TIMING_REG1_0`.
+    timing1: u32,
+    /// The value of `SPI_TIMING_REG2_0`.
+    timing2: u32,
+}
+
+/// A snapshot of the chip-select configuration of an SPI controller.
+///
+/// Obtained through [`Spi::save_cs_state`] and applied through [`Spi::restore_cs_state`].
+///
+/// [`Spi::save_cs_state`]: struct.Spi.html#method.save_cs_state
+/// [`Spi::restore_cs_state`]: struct.Spi.html#method.restore_cs_state
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CsState {
+    /// The `CS_SEL`, `CS_POL_INACTIVE_*`, `MODE`, `IDLE_SDA`, `CS_SW_HW` and `CS_SW_VAL` bits of `SPI_COMMAND_0`.
+    command: u32,
+}
+
+/// Chip-select timing of an SPI controller, in clock cycles.
+///
+/// Applied to the selected chip-select line through [`Spi::set_cs_timing`].
+/// Every parameter maps to a field of the timing registers, for the line
+/// `n` that is selected through `CS_SEL`:
+///
+/// - `setup_cycles` is `CS_SETUP_TIME_n` of `SPI_TIMING_REG1_0`, up to 15.
+/// - `hold_cycles` is `CS_HOLD_TIME_n` of `SPI_TIMING_REG1_0`, up to 15.
+/// - `inactive_cycles` is `CYCLES_BETWEEN_PACKETS_n` of `SPI_TIMING_REG2_0`, up to 31.
+///
+/// [`Spi::set_cs_timing`]: struct.Spi.html#method.set_cs_timing
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CsTiming {
+    /// The cycles between asserting chip-select and the first clock edge.
+    pub setup_cycles: u8,
+    /// The cycles between the last clock edge and deasserting chip-select.
+    pub hold_cycles: u8,
+    /// The cycles for chip-select to stay inactive between two packets.
+    pub inactive_cycles: u8,
+}
+
+/// A complete bus configuration of an SPI controller.
+///
+/// Applied at once through [`Spi::init_with`]. The configuration can be
+/// built up from the defaults, which match the state of a controller after
+/// [`Spi::init`]:
+///
+/// ```no_run
+/// use libtegra::spi::{ChipSelect, Spi, SpiConfig, SpiMode};
+///
+/// let config = SpiConfig::new()
+///     .mode(SpiMode::Mode3)
+///     .chip_select(ChipSelect::Cs1)
+///     .frequency(10_000_000);
+///
+/// Spi::SPI4.init_with(config).unwrap();
+/// ```
+///
+/// [`Spi::init_with`]: struct.Spi.html#method.init_with
+/// [`Spi::init`]: struct.Spi.html#method.init
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpiConfig {
+    /// The clock polarity and phase, see [`Spi::set_mode`].
+    ///
+    /// [`Spi::set_mode`]: struct.Spi.html#method.set_mode
+    pub mode: SpiMode,
+    /// The order of the bits in a word, see [`Spi::set_bit_order`].
+    ///
+    /// [`Spi::set_bit_order`]: struct.Spi.html#method.set_bit_order
+    pub bit_order: BitOrder,
+    /// The length of the words in bits, see [`Spi::set_word_length`].
+    ///
+    /// [`Spi::set_word_length`]: struct.Spi.html#method.set_word_length
+    pub word_length: u8,
+    /// The chip-select line, see [`Spi::set_chip_select`].
+    ///
+    /// [`Spi::set_chip_select`]: struct.Spi.html#method.set_chip_select
+    pub chip_select: ChipSelect,
+    /// The requested clock rate in Hz, see [`Spi::set_frequency`].
+    ///
+    /// [`Spi::set_frequency`]: struct.Spi.html#method.set_frequency
+    pub frequency: u32,
+}
+
+impl SpiConfig {
+    /// Creates the default configuration.
+    pub fn new() -> Self {
+        SpiConfig {
+            mode: SpiMode::Mode0,
+            bit_order: BitOrder::MsbFirst,
+            word_length: 8,
+            chip_select: ChipSelect::Cs0,
+            frequency: DEFAULT_FREQUENCY,
+        }
+    }
+
+    /// Sets the clock polarity and phase.
+    pub fn mode(mut self, mode: SpiMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the order of the bits in a word.
+    pub fn bit_order(mut self, bit_order: BitOrder) -> Self {
+        self.bit_order = bit_order;
+        self
+    }
+
+    /// Sets the length of the words in bits.
+    pub fn word_length(mut self, bits: u8) -> Self {
+        self.word_length = bits;
+        self
+    }
+
+    /// Sets the chip-select line.
+    pub fn chip_select(mut self, cs: ChipSelect) -> Self {
+        self.chip_select = cs;
+        self
+    }
+
+    /// Sets the requested clock rate in Hz.
+    pub fn frequency(mut self, hz: u32) -> Self {
+        self.frequency = hz;
+        self
+    }
+}
+
+impl Default for SpiConfig {
+    fn default() -> Self {
+        SpiConfig::new()
+    }
+}
+
+/// The pattern that is transferred by [`Spi::self_test`].
+///
+/// [`Spi::self_test`]: struct.Spi.html#method.self_test
+const SELF_TEST_PATTERN: [u8; 8] = [0x00, 0xFF, 0xA5, 0x5A, 0x01, 0x80, 0x3C, 0xC3];
+
+/// The rate of the `PLLP_OUT0` clock that feeds the controllers, in Hz.
+const PLLP_OUT0_RATE: u32 = 408_000_000;
+
+/// The `CLK_SOURCE_SPIx` source selection of `PLLP_OUT0`.
+const CLK_SOURCE_PLLP_OUT0: u32 = 0;
+
+/// The `CLK_SOURCE_SPIx` source selection of `CLK_M`.
+const CLK_SOURCE_CLK_M: u32 = 6;
+
+/// The clock rate of the controllers after initialization, in Hz.
+pub const DEFAULT_FREQUENCY: u32 = 25_500_000;
+
+/// The maximum clock rate of the controllers, in Hz.
+pub const MAX_FREQUENCY: u32 = 65_000_000;
+
+/// The minimum clock rate of the controllers, in Hz.
+///
+/// Rates that `PLLP_OUT0` can't be divided down to are derived from `CLK_M`
+/// instead. This is the rate of a 38.4 MHz `CLK_M` divided by the maximum
+/// divisor of the 7.1 fixed-point clock divider, which is 128.5, so about
+/// 299 kHz. With a 12 MHz `CLK_M`, rates down to about 93 kHz are supported.
+pub const MIN_FREQUENCY: u32 = 38_400_000 * 2 / (0xFF + 2);
+
+/// The default time in microseconds that a transfer may take before it is aborted.
+pub const DEFAULT_TIMEOUT_US: u32 = 5_000;
+
+/// The depth of the TX FIFO and the RX FIFO, in words.
+const FIFO_DEPTH: usize = 64;
+
+/// The minimum length of a PIO transfer, in bytes, from which on packed mode is used.
+///
+/// In packed mode, four bytes share a single FIFO word, which reduces the
+/// number of FIFO accesses for bulk transfers.
+pub const PACKED_THRESHOLD: usize = 16;
+
+/// The size of the scratch buffer that [`Spi::exchange`] receives into, in bytes.
+///
+/// This equals the depth of the FIFOs in unpacked 8-bit mode, so every
+/// exchange is carried out as a single block.
+///
+/// [`Spi::exchange`]: struct.Spi.html#method.exchange
+pub const EXCHANGE_BUFFER_SIZE: usize = FIFO_DEPTH;
+
+/// Packs up to four bytes into a FIFO word, padding missing bytes with `0`.
+fn pack_word(bytes: &[u8]) -> u32 {
+    let mut word = [0; 4];
+    word[..bytes.len()].copy_from_slice(bytes);
+
+    u32::from_le_bytes(word)
+}
+
+/// Gets the amount of bytes of a PIO transfer that can be transferred in packed mode.
+///
+/// Packed mode is used for transfers of at least `threshold` bytes if the word
+/// length allows it, and covers all whole FIFO words of the transfer.
+fn pio_packed_length(length: usize, packable: bool, threshold: usize) -> usize {
+    if packable && length >= threshold {
+        length & !3
+    } else {
+        0
+    }
+}
+
+/// Computes the 7.1 fixed-point divisor for deriving a clock rate from a `parent` clock.
+///
+/// The closest rate that doesn't exceed `hz` is chosen and returned along
+/// with the divisor. If `hz` is below the lowest rate that the divider can
+/// produce, `None` is returned.
+fn clock_divisor(parent: u32, hz: u32) -> Option<(u32, u32)> {
+    if hz == 0 {
+        return None;
+    }
+
+    // Round the divisor up to not exceed the requested rate.
+    let divisor = ((parent * 2 + hz - 1) / hz).saturating_sub(2);
+    if divisor > 0xFF {
+        return None;
+    }
+
+    Some((divisor, parent * 2 / (divisor + 2)))
+}
+
+/// Converts an amount of cycles of a clock with a given rate into microseconds, rounding up.
+fn clock_cycles_to_us(rate: u32, cycles: u32) -> u32 {
+    ((u64::from(cycles) * 1_000_000 + u64::from(rate) - 1) / u64::from(rate)) as u32
+}
+
+/// Gets the rate of the `CLK_M` clock, in Hz.
+///
+/// If the oscillator frequency can't be detected, the common 38.4 MHz is assumed.
+fn clk_m_rate() -> u32 {
+    match detect_clk_m_freq() {
+        Some(ClkMFreq::Mhz12) => 12_000_000,
+        _ => 38_400_000,
+    }
+}
+
+/// Splits a PIO transfer of `length` bytes into blocks that fit into the FIFOs.
+///
+/// The first `packed_length` bytes are transferred in packed mode, with four
+/// bytes per FIFO word, and the remainder in unpacked mode, with a single word
+/// of `bytes_per_packet` bytes per FIFO word. The offset, the length and the
+/// mode of every block are yielded in order.
+fn pio_blocks(
+    length: usize,
+    packed_length: usize,
+    bytes_per_packet: usize,
+) -> impl Iterator<Item = (usize, usize, bool)> {
+    let mut offset = 0;
+
+    iter::from_fn(move || {
+        if offset >= length {
+            return None;
+        }
+
+        let (block, packed) = if offset < packed_length {
+            ((packed_length - offset).min(FIFO_DEPTH * 4), true)
+        } else {
+            ((length - offset).min(FIFO_DEPTH * bytes_per_packet), false)
+        };
+
+        let item = (offset, block, packed);
+        offset += block;
+
+        Some(item)
+    })
+}
+
+/// Reinterprets an initialized buffer as a buffer of `MaybeUninit` bytes.
+///
+/// This is sound as long as the buffer is only ever written with initialized values.
+fn as_uninit(buf: &mut [u8]) -> &mut [MaybeUninit<u8>] {
+    unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) }
+}
+
+/// Driver state of a controller that isn't held by its registers.
+struct ControllerState {
+    /// Timestamp of the last transfer activity, in microseconds.
+    last_activity: AtomicU32,
+    /// Minimum chip-select inactive time between transfers that is enforced
+    /// in software, in microseconds.
+    min_cs_inactive_us: AtomicU32,
+    /// Transfer timeout, in microseconds.
+    timeout_us: AtomicU32,
+    /// Whether chip-select is managed manually.
+    manual_cs: AtomicBool,
+    /// Clock rate in Hz, or `0` if unknown.
+    clock_rate: AtomicU32,
+    /// Whether an exchange is using the scratch buffer.
+    exchanging: AtomicBool,
+    /// The scratch buffer that exchanges receive into.
+    scratch: UnsafeCell<[u8; EXCHANGE_BUFFER_SIZE]>,
+}
+
+// SAFETY: The scratch buffer is only accessed by the exchange that claimed it.
+unsafe impl Sync for ControllerState {}
+
+impl ControllerState {
+    /// Creates the state of a controller that hasn't been configured yet.
+    const fn new() -> Self {
+        ControllerState {
+            last_activity: AtomicU32::new(0),
+            min_cs_inactive_us: AtomicU32::new(0),
+            timeout_us: AtomicU32::new(DEFAULT_TIMEOUT_US),
+            manual_cs: AtomicBool::new(false),
+            clock_rate: AtomicU32::new(0),
+            exchanging: AtomicBool::new(false),
+            scratch: UnsafeCell::new([0; EXCHANGE_BUFFER_SIZE]),
+        }
+    }
+}
+
+/// Driver states of the controllers 1 through 4.
+static STATES: [ControllerState; 4] = [
+    ControllerState::new(),
+    ControllerState::new(),
+    ControllerState::new(),
+    ControllerState::new(),
+];
+
 /// Representation of an SPI.
 ///
 /// NOTE: Instances of this structure should never be created manually.
 /// Refer to the public constants this structure holds, which represent
 /// the controllers 1 through 4.
+///
+/// Configuration of the controller must happen between transfers.
+/// Changing the configuration while a transfer is in flight would
+/// corrupt it, hence configuration methods refuse to do so and fail
+/// with [`SpiError::Busy`] instead.
+///
+/// [`SpiError::Busy`]: enum.SpiError.html#variant.Busy
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Spi {
     /// A pointer to the [`Registers`] of the device.
@@ -21,198 +580,3169 @@ pub struct Spi {
     registers: *const Registers,
 }
 
+// Definitions of known SPIs.
+
 impl Spi {
-    /// Waits for the SPI Controller to complete all transactions.
-    fn wait_until_ready(&self) {
-        let controller = unsafe { &*self.registers };
+    /// Representation of SPI 1, whose registers are mapped at `0x7000_D400`.
+    pub const SPI1: Self = Spi {
+        registers: SPI_1_REGISTERS,
+    };
 
-        while !controller.SPI_TRANSFER_STATUS_0.is_set(SPI_TRANSFER_STATUS_0::RDY) {
-            // Wait until all transactions are completed.
+    /// Representation of SPI 2, whose registers are mapped at `0x7000_D600`.
+    pub const SPI2: Self = Spi {
+        registers: SPI_2_REGISTERS,
+    };
+
+    /// Representation of SPI 3, whose registers are mapped at `0x7000_D800`.
+    pub const SPI3: Self = Spi {
+        registers: SPI_3_REGISTERS,
+    };
+
+    /// Representation of SPI 4, whose registers are mapped at `0x7000_DA00`.
+    pub const SPI4: Self = Spi {
+        registers: SPI_4_REGISTERS,
+    };
+}
+
+impl Spi {
+    /// Gets the index of the controller, starting from 0 for SPI 1.
+    #[inline(always)]
+    fn index(&self) -> usize {
+        ((self.registers as u32 - SPI_1) / 0x200) as usize
+    }
+
+    /// Gets the driver state of the controller.
+    #[inline(always)]
+    fn state(&self) -> &'static ControllerState {
+        // Mocks carry their own state.
+        #[cfg(any(test, feature = "mock"))]
+        {
+            if let Some(state) = mock::state(self.registers) {
+                return state;
+            }
         }
+
+        &STATES[self.index()]
     }
 
-    /// Clears the error status bits of the [`SPI_FIFO_STATUS_0`] register.
-    ///
-    /// [`SPI_FIFO_STATUS_0`]: ./SPI_FIFO_STATUS_0/index.html
-    fn clear_fifo_status(&self) {
-        let controller = unsafe { &*self.registers };
+    /// Gets the CAR clock of the controller.
+    fn clock(&self) -> &'static Clock {
+        match self.index() {
+            0 => &Clock::SPI_1,
+            1 => &Clock::SPI_2,
+            2 => &Clock::SPI_3,
+            _ => &Clock::SPI_4,
+        }
+    }
 
-        // Clear the relevant bits.
-        controller.SPI_FIFO_STATUS_0.modify(
-            SPI_FIFO_STATUS_0::ERR::CLEAR
-            + SPI_FIFO_STATUS_0::TX_FIFO_OVF::CLEAR
-            + SPI_FIFO_STATUS_0::TX_FIFO_UNR::CLEAR
-            + SPI_FIFO_STATUS_0::RX_FIFO_OVF::CLEAR
-            + SPI_FIFO_STATUS_0::RX_FIFO_UNR::CLEAR
-        );
+    /// Gets the APB DMA request selector of the controller.
+    fn dma_request(&self) -> u32 {
+        // The SL2B1 through SL2B4 requesters are numbered consecutively from 15.
+        15 + self.index() as u32
     }
 
-    /// Transmits data over SPI in PIO mode.
-    ///
-    /// NOTE: This method is a low-level implementation
-    /// of the SPI transmit flow and doesn't validate any
-    /// buffer boundaries. This task is delegated to the
-    /// caller.
-    fn pio_send_packet(&self, data: &[u8]) -> Result<(), ()> {
+    /// Records the current time as the last transfer activity of the controller.
+    #[inline(always)]
+    fn mark_activity(&self) {
+        self.state().last_activity.store(get_microseconds(), Ordering::Relaxed);
+    }
+
+    /// Gets the configured transfer timeout of the controller, in microseconds.
+    #[inline(always)]
+    fn timeout_us(&self) -> u32 {
+        self.state().timeout_us.load(Ordering::Relaxed)
+    }
+
+    /// Drives chip-select to its active or inactive level.
+    fn drive_cs(&self, active: bool) {
         let controller = unsafe { &*self.registers };
 
+        // Determine the inactive level of the selected line.
+        let inactive_high = match controller.SPI_COMMAND_0.read(SPI_COMMAND_0::CS_SEL) {
+            0 => controller.SPI_COMMAND_0.is_set(SPI_COMMAND_0::CS_POL_INACTIVE_0),
+            1 => controller.SPI_COMMAND_0.is_set(SPI_COMMAND_0::CS_POL_INACTIVE_1),
+            2 => controller.SPI_COMMAND_0.is_set(SPI_COMMAND_0::CS_POL_INACTIVE_2),
+            _ => controller.SPI_COMMAND_0.is_set(SPI_COMMAND_0::CS_POL_INACTIVE_3),
+        };
+
+        // Drive the line to the opposite level to assert it.
+        controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::CS_SW_VAL.val((active != inactive_high) as u32));
+    }
+
+    /// Opens the frame of a transfer.
+    ///
+    /// The FIFOs are flushed, chip-select is kept inactive for the minimum
+    /// time and the start of the transfer is noted. Then, chip-select is
+    /// asserted, unless it is managed manually. As chip-select is driven in
+    /// software, the setup time is waited for here. Every frame that was
+    /// opened successfully must be closed through [`Spi::close_frame`].
+    ///
+    /// [`Spi::close_frame`]: struct.Spi.html#method.close_frame
+    fn open_frame(&self) -> Result<(), SpiError> {
         // Flush the FIFOs.
-        self.flush_fifos();
+        self.flush_fifos()?;
 
-        // Set 8-bit transfers, unpacked mode, most significant bit first.
-        controller.SPI_COMMAND_0.modify(
-            SPI_COMMAND_0::PACKED::CLEAR
-            + SPI_COMMAND_0::BIT_LEN.val(7)
-        );
+        // Keep chip-select inactive for the minimum time.
+        self.wait_min_cs_inactive();
 
-        // Set the size of data blocks to be transferred.
-        controller.SPI_DMA_BLK_SIZE_0.set((data.len() - 1) as u32);
+        // Note the start of the transfer.
+        self.mark_activity();
 
-        // Clear SPI_TRANSFER_STATUS RDY bit.
-        controller.SPI_TRANSFER_STATUS_0.modify(SPI_TRANSFER_STATUS_0::RDY::CLEAR);
+        // Assert chip-select, unless it is managed manually.
+        if !self.state().manual_cs.load(Ordering::Relaxed) {
+            self.drive_cs(true);
 
-        // Set the transmit enable bit.
-        controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::TX_EN::SET);
+            let setup_cycles = self.cs_timing().setup_cycles;
+            if setup_cycles != 0 {
+                usleep(self.cycles_to_us(u32::from(setup_cycles)));
+            }
+        }
 
-        // Load in the data to write.
-        let packet = u32::from_le_bytes(data.try_into().unwrap());
-        controller.SPI_TX_FIFO_0.set(packet);
+        Ok(())
+    }
 
-        // Make sure that the register is stabilized before setting the PIO bit.
-        usleep(2);
+    /// Closes the frame of a transfer that was opened through [`Spi::open_frame`].
+    ///
+    /// Chip-select is deasserted after the hold time, unless it is managed
+    /// manually, and the completion of the transfer is noted.
+    ///
+    /// [`Spi::open_frame`]: struct.Spi.html#method.open_frame
+    fn close_frame(&self) {
+        // Deassert chip-select, unless it is managed manually.
+        if !self.state().manual_cs.load(Ordering::Relaxed) {
+            let hold_cycles = self.cs_timing().hold_cycles;
+            if hold_cycles != 0 {
+                usleep(self.cycles_to_us(u32::from(hold_cycles)));
+            }
 
-        // Set the PIO bit to start transaction.
-        controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::PIO::Go);
+            self.drive_cs(false);
+        }
 
-        // Delay for a few CPU cycles to process the data.
-        usleep(1);
+        // Note the completion of the transfer.
+        self.mark_activity();
+    }
 
-        // Dummy read.
-        controller.SPI_COMMAND_0.get();
+    /// Carries out a blocking transfer within its own frame.
+    ///
+    /// The frame is closed regardless of the result of `transfer`.
+    fn framed<T, F: FnOnce() -> Result<T, SpiError>>(&self, transfer: F) -> Result<T, SpiError> {
+        self.open_frame()?;
 
-        // Wait for the transaction to complete.
-        self.wait_until_ready();
+        let result = transfer();
 
-        // Clear the transmit enable bit.
-        controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::TX_EN::CLEAR);
+        self.close_frame();
 
-        // Check for errors.
-        if controller.SPI_FIFO_STATUS_0.is_set(SPI_FIFO_STATUS_0::ERR) {
-            self.clear_fifo_status();
-            return Err(());
-        }
+        result
+    }
 
-        Ok(())
+    /// Waits until chip-select has been inactive for the minimum time enforced in software.
+    fn wait_min_cs_inactive(&self) {
+        let min_inactive = u64::from(self.state().min_cs_inactive_us.load(Ordering::Relaxed));
+
+        while self.idle_duration_us() < min_inactive {
+            // Wait for the inactive time to pass.
+        }
     }
 
-    /// Receives data over SPI in PIO mode.
+    /// Converts an amount of clock cycles of the controller into microseconds, rounding up.
     ///
-    /// NOTE: This method is a low-level implementation
-    /// of the SPI receive flow and doesn't validate any
-    /// buffer boundaries. This task is delegated to the
-    /// caller.
-    fn pio_receive_packet(&self, data: &mut [u8]) -> Result<(), ()> {
-        let controller = unsafe { &*self.registers };
-
-        // Flush the FIFOs.
-        self.flush_fifos();
+    /// If the clock rate of the controller is unknown, the slowest
+    /// supported rate is assumed, which yields a conservative delay.
+    fn cycles_to_us(&self, cycles: u32) -> u32 {
+        let rate = match self.state().clock_rate.load(Ordering::Relaxed) {
+            0 => MIN_FREQUENCY,
+            rate => rate,
+        };
 
-        // Set 8-bit transfers, unpacked mode, most significant bit first.
-        controller.SPI_COMMAND_0.modify(
-            SPI_COMMAND_0::PACKED::CLEAR
-            + SPI_COMMAND_0::BIT_LEN.val(7)
-        );
+        clock_cycles_to_us(rate, cycles)
+    }
 
-        // Set the size of data blocks to be transferred.
-        controller.SPI_DMA_BLK_SIZE_0.set((data.len() - 1) as u32);
+    /// Gets the delay in microseconds that the controller needs to latch a new configuration.
+    ///
+    /// The controller latches its configuration with its own clock, so a few
+    /// cycles of it must pass.
+    fn setup_delay_us(&self) -> u32 {
+        self.cycles_to_us(4)
+    }
 
-        // Clear SPI_TRANSFER_STATUS RDY bit.
-        controller.SPI_TRANSFER_STATUS_0.modify(SPI_TRANSFER_STATUS_0::RDY::CLEAR);
+    /// Starts a prepared PIO transaction.
+    ///
+    /// The controller samples the whole configuration, i.e. `SPI_COMMAND_0`,
+    /// `SPI_DMA_BLK_SIZE_0` and the loaded TX FIFO, at the moment the `PIO`
+    /// bit is set. Therefore, the `PIO` bit must be set in a separate write
+    /// after all configuration writes have reached the controller, otherwise
+    /// a spurious transfer with a stale configuration is started.
+    ///
+    /// # Memory ordering
+    ///
+    /// No explicit fences are needed around the register accesses of the
+    /// PIO routines. All accessors of the `register` crate are volatile,
+    /// which the compiler never reorders against each other, and the SPI
+    /// registers are mapped as Device memory, for which the CPU issues
+    /// accesses to the same peripheral in program order. What is not
+    /// guaranteed is *when* a posted write takes effect, which is why the
+    /// configuration is read back before the `PIO` bit is set.
+    ///
+    /// NOTE: This reasoning only holds for register accesses. Ordering
+    /// accesses to normal memory, like DMA buffers, against the controller
+    /// requires a barrier, which the [`cache`] maintenance functions issue.
+    ///
+    /// [`cache`]: ../cache/index.html
+    fn start_pio(&self) {
+        let controller = unsafe { &*self.registers };
 
-        // Set the receive enable bit.
-        controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::RX_EN::SET);
+        // Register writes are posted on the APB bus, so read back
+        // the configuration to make sure that it has been applied.
+        controller.SPI_COMMAND_0.get();
 
-        // Make sure that the register is stabilized before setting the PIO bit.
-        usleep(2);
+        // Give the controller time to latch the configuration.
+        usleep(self.setup_delay_us());
 
         // Set the PIO bit to start transaction.
         controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::PIO::Go);
 
-        // Delay for a few CPU cycles to process the data.
-        usleep(1);
-
-        // Dummy read.
+        // Dummy read to make sure the PIO bit has been applied as well.
         controller.SPI_COMMAND_0.get();
+    }
 
-        // Wait for the transaction to complete.
-        self.wait_until_ready();
+    /// Waits for the current PIO transaction to complete within the configured timeout.
+    ///
+    /// If the transaction doesn't complete in time, it is aborted and
+    /// [`SpiError::Timeout`] is returned. Otherwise, the FIFO status is
+    /// checked for errors.
+    ///
+    /// [`SpiError::Timeout`]: enum.SpiError.html#variant.Timeout
+    fn wait_until_complete(&self) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
 
-        // Clear the receive enable bit.
-        controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::RX_EN::CLEAR);
+        if let Err(error) = self.wait_until_ready_timeout(self.timeout_us()) {
+            #[cfg(feature = "defmt")]
+            defmt::warn!(
+                "SPI{}: transfer timed out after {} us",
+                self.index() + 1,
+                self.timeout_us()
+            );
 
-        // Check for errors.
-        if controller.SPI_FIFO_STATUS_0.is_set(SPI_FIFO_STATUS_0::ERR) {
-            self.clear_fifo_status();
-            return Err(());
+            // Abort the transaction.
+            controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::PIO::Stop);
+
+            return Err(error);
         }
 
-        // Read the data bytes into the buffer.
-        for i in data.iter_mut() {
-            *i = controller.SPI_RX_FIFO_0.get() as u8;
+        // Check for errors.
+        self.check_fifo_errors()
+    }
+
+    /// Makes sure that no transfer is in flight before the configuration is changed.
+    ///
+    /// A transfer is considered in flight as long as the controller didn't clear
+    /// the `PIO` bit of `SPI_COMMAND_0` or the `DMA` bit of `SPI_DMA_CTL_0` yet.
+    fn ensure_idle(&self) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+
+        if controller.SPI_COMMAND_0.is_set(SPI_COMMAND_0::PIO)
+            || controller.SPI_DMA_CTL_0.is_set(SPI_DMA_CTL_0::DMA)
+        {
+            return Err(SpiError::Busy);
         }
 
         Ok(())
     }
 
+    /// Clears the error status bits of the [`SPI_FIFO_STATUS_0`] register.
+    ///
+    /// [`SPI_FIFO_STATUS_0`]: ./SPI_FIFO_STATUS_0/index.html
+    fn clear_fifo_status(&self) {
+        let controller = unsafe { &*self.registers };
+
+        // Clear the relevant bits.
+        controller.SPI_FIFO_STATUS_0.modify(
+            SPI_FIFO_STATUS_0::ERR::CLEAR
+            + SPI_FIFO_STATUS_0::TX_FIFO_OVF::CLEAR
+            + SPI_FIFO_STATUS_0::TX_FIFO_UNR::CLEAR
+            + SPI_FIFO_STATUS_0::RX_FIFO_OVF::CLEAR
+            + SPI_FIFO_STATUS_0::RX_FIFO_UNR::CLEAR
+        );
+    }
+
+    /// Checks the `SPI_FIFO_STATUS_0` register for errors.
+    ///
+    /// If an error is detected, it is mapped to the most specific
+    /// [`SpiError`] variant. Then, the status bits are cleared and
+    /// the FIFOs are flushed, so the controller is ready for the
+    /// next transfer.
+    ///
+    /// [`SpiError`]: enum.SpiError.html
+    fn check_fifo_errors(&self) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+        let status = controller.SPI_FIFO_STATUS_0.extract();
+
+        if status.is_set(SPI_FIFO_STATUS_0::ERR) {
+            let error = if status.is_set(SPI_FIFO_STATUS_0::TX_FIFO_OVF) {
+                SpiError::TxOverflow
+            } else if status.is_set(SPI_FIFO_STATUS_0::TX_FIFO_UNR) {
+                SpiError::TxUnderrun
+            } else if status.is_set(SPI_FIFO_STATUS_0::RX_FIFO_OVF) {
+                SpiError::RxOverflow
+            } else if status.is_set(SPI_FIFO_STATUS_0::RX_FIFO_UNR) {
+                SpiError::RxUnderrun
+            } else {
+                SpiError::FifoError
+            };
+
+            #[cfg(feature = "defmt")]
+            defmt::warn!(
+                "SPI{}: FIFO error {}, status {=u32:#x}",
+                self.index() + 1,
+                error,
+                status.get()
+            );
+
+            self.clear_fifo_status();
+            // The FIFO error takes precedence over a failed flush.
+            let _ = self.flush_fifos();
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    /// Validates that a buffer can be transferred over DMA.
+    ///
+    /// DMA transfers are carried out in packed mode, four bytes
+    /// per FIFO word, so the buffer must be word-aligned and its
+    /// length must be a non-zero multiple of 4 that fits into the
+    /// 16-bit `SPI_DMA_BLK_SIZE_0` register.
+    fn check_dma_buffer(address: usize, length: usize) -> Result<(), SpiError> {
+        if address % 4 != 0 {
+            return Err(SpiError::UnalignedBuffer);
+        }
+
+        if length > 0x10000 {
+            return Err(SpiError::BufferTooLarge);
+        }
+
+        if length == 0 || length % 4 != 0 {
+            return Err(SpiError::InvalidLength);
+        }
+
+        Ok(())
+    }
+
+    /// Starts a prepared DMA transfer and blocks until it is completed.
+    ///
+    /// If the transfer doesn't complete within the configured timeout, e.g.
+    /// because the DMA request line stalls, the channel is stopped, the
+    /// FIFOs are flushed and [`SpiError::Timeout`] is returned.
+    ///
+    /// [`SpiError::Timeout`]: enum.SpiError.html#variant.Timeout
+    fn dma_execute(&self, channel: &Channel) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+
+        // Start the DMA channel.
+        channel.start();
+
+        // The DMA channel moves one word per burst, so the FIFO trigger levels
+        // are matched to that. Then, enable DMA mode to start the transaction.
+        controller.SPI_DMA_CTL_0.modify(
+            SPI_DMA_CTL_0::TX_TRIG::OneWord
+            + SPI_DMA_CTL_0::RX_TRIG::OneWord
+            + SPI_DMA_CTL_0::DMA::SET
+        );
+
+        // Wait for the DMA transfer to complete.
+        let result = wait_until(
+            || !controller.SPI_DMA_CTL_0.is_set(SPI_DMA_CTL_0::DMA) && !channel.is_busy(),
+            self.timeout_us(),
+        );
+
+        if let Err(error) = result {
+            #[cfg(feature = "defmt")]
+            defmt::warn!(
+                "SPI{}: DMA transfer timed out after {} us",
+                self.index() + 1,
+                self.timeout_us()
+            );
+
+            // Abort the transaction.
+            controller.SPI_DMA_CTL_0.modify(SPI_DMA_CTL_0::DMA::CLEAR);
+            channel.finish();
+
+            // Discard what is left in the FIFOs. The controller may not become
+            // ready after an aborted transfer, so don't wait for it to be idle.
+            controller.SPI_FIFO_STATUS_0.modify(
+                SPI_FIFO_STATUS_0::RX_FIFO_FLUSH::SET + SPI_FIFO_STATUS_0::TX_FIFO_FLUSH::SET,
+            );
+            let _ = wait_until(
+                || {
+                    !controller.SPI_FIFO_STATUS_0.is_set(SPI_FIFO_STATUS_0::RX_FIFO_FLUSH)
+                        && !controller.SPI_FIFO_STATUS_0.is_set(SPI_FIFO_STATUS_0::TX_FIFO_FLUSH)
+                },
+                self.timeout_us(),
+            );
+
+            return Err(error.into());
+        }
+
+        // Finish the DMA transfer.
+        channel.finish();
+
+        Ok(())
+    }
+
+    /// Transfers data over SPI in PIO mode.
+    ///
+    /// Either direction is enabled depending on whether the respective
+    /// buffer is provided. The transfer is split into blocks that fit into
+    /// the FIFOs. If the transfer is at least `packed_threshold` bytes long
+    /// and the word length allows it, the bulk of it is carried out in packed
+    /// mode and only a trailing partial FIFO word falls back to unpacked mode.
+    ///
+    /// The length of the buffers must be a multiple of the bytes per word,
+    /// otherwise [`SpiError::InvalidLength`] is returned.
+    ///
+    /// The receive buffer doesn't need to be initialized. It is guaranteed
+    /// to be fully initialized once this method returns successfully.
+    ///
+    /// [`SpiError::InvalidLength`]: enum.SpiError.html#variant.InvalidLength
+    fn pio_transfer(
+        &self,
+        tx: Option<&[u8]>,
+        rx: Option<&mut [MaybeUninit<u8>]>,
+        packed_threshold: usize,
+    ) -> Result<(), SpiError> {
+        let length = tx.map_or(0, |tx| tx.len()).max(rx.as_ref().map_or(0, |rx| rx.len()));
+
+        let bytes_per_packet = self.bytes_per_packet();
+
+        if length == 0 {
+            return Ok(());
+        }
+
+        if length % bytes_per_packet != 0 {
+            return Err(SpiError::InvalidLength);
+        }
+
+        self.framed(|| self.pio_phase(tx, rx, length, packed_threshold))
+    }
+
+    /// Carries out a single phase of a PIO transfer of `length` bytes.
+    ///
+    /// Unlike [`Spi::pio_transfer`], this neither flushes the FIFOs nor
+    /// drives chip-select, so that multiple phases can be chained within
+    /// the same frame. The length must have been validated by the caller.
+    ///
+    /// [`Spi::pio_transfer`]: struct.Spi.html#method.pio_transfer
+    fn pio_phase(
+        &self,
+        tx: Option<&[u8]>,
+        mut rx: Option<&mut [MaybeUninit<u8>]>,
+        length: usize,
+        packed_threshold: usize,
+    ) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+        let bytes_per_packet = self.bytes_per_packet();
+
+        // Set the transmit and receive enable bits for the requested directions.
+        controller.SPI_COMMAND_0.modify(
+            SPI_COMMAND_0::TX_EN.val(tx.is_some() as u32)
+            + SPI_COMMAND_0::RX_EN.val(rx.is_some() as u32)
+        );
+
+        // Determine the amount of bytes that can be transferred in packed mode.
+        let packed_length = pio_packed_length(length, self.is_packable(), packed_threshold);
+
+        let mut result = Ok(());
+        for (offset, block, packed) in pio_blocks(length, packed_length, bytes_per_packet) {
+            result = self.pio_block(
+                tx.map(|tx| tx.get(offset..).unwrap_or(&[])),
+                rx.as_mut().map(|rx| rx.get_mut(offset..).unwrap_or(&mut [])),
+                block,
+                packed,
+            );
+
+            if result.is_err() {
+                break;
+            }
+        }
+
+        // Clear both, the transmit and the receive enable bits.
+        controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::TX_EN::CLEAR + SPI_COMMAND_0::RX_EN::CLEAR);
+
+        result
+    }
+
+    /// Transfers a single block of `length` bytes through the FIFOs in PIO mode.
+    ///
+    /// In packed mode, four bytes are moved per FIFO word and a trailing
+    /// partial word is packed from however many bytes remain. Otherwise,
+    /// every word occupies a FIFO word on its own. Either way, the block
+    /// must fit into the FIFOs and consist of whole words.
+    ///
+    /// Words are stored in little-endian byte order in the buffers. Bytes
+    /// missing from `tx` are transmitted as dummy `0`s and bytes that don't
+    /// fit into `rx` are discarded.
+    fn pio_block(
+        &self,
+        tx: Option<&[u8]>,
+        rx: Option<&mut [MaybeUninit<u8>]>,
+        length: usize,
+        packed: bool,
+    ) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+
+        // Load the block and start the transaction.
+        self.pio_start_block(tx, length, packed);
+
+        // Wait for the transaction to complete and check for errors.
+        self.wait_until_complete()?;
+
+        // Read the received data into the buffer, discarding the surplus.
+        if let Some(rx) = rx {
+            let word_size = self.bytes_per_fifo_word() as usize;
+            let mask = self.fifo_word_mask();
+
+            for offset in (0..length).step_by(word_size) {
+                let word = (controller.SPI_RX_FIFO_0.get() & mask).to_le_bytes();
+                if let Some(bytes) = rx.get_mut(offset..rx.len().min(offset + word_size)) {
+                    for (byte, value) in bytes.iter_mut().zip(word.iter()) {
+                        *byte = MaybeUninit::new(*value);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the mask of the significant bits of a FIFO word.
+    ///
+    /// In packed mode, all bits are significant. Otherwise, only the bits
+    /// of a single word are.
+    fn fifo_word_mask(&self) -> u32 {
+        let controller = unsafe { &*self.registers };
+
+        if controller.SPI_COMMAND_0.is_set(SPI_COMMAND_0::PACKED) {
+            u32::max_value()
+        } else {
+            u32::max_value() >> (32 - self.word_length())
+        }
+    }
+
+    /// Loads a single block of `length` bytes into TX FIFO and starts the transaction in PIO mode.
+    ///
+    /// This doesn't wait for the transaction to complete. Refer to
+    /// [`Spi::pio_block`] for the layout of the block.
+    ///
+    /// [`Spi::pio_block`]: struct.Spi.html#method.pio_block
+    fn pio_start_block(&self, tx: Option<&[u8]>, length: usize, packed: bool) {
+        let controller = unsafe { &*self.registers };
+
+        // Set packed or unpacked mode.
+        controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::PACKED.val(packed as u32));
+
+        // Determine how many bytes are moved per FIFO access and which bits of
+        // a FIFO word are significant.
+        let word_size = self.bytes_per_fifo_word() as usize;
+        let mask = self.fifo_word_mask();
+
+        // Set the size of data blocks to be transferred, in packets.
+        controller.SPI_DMA_BLK_SIZE_0.set((length / self.bytes_per_packet() - 1) as u32);
+
+        // Clear SPI_TRANSFER_STATUS RDY bit.
+        controller.SPI_TRANSFER_STATUS_0.modify(SPI_TRANSFER_STATUS_0::RDY::CLEAR);
+
+        // Load in the data to write, padded with dummy bytes.
+        if let Some(tx) = tx {
+            for offset in (0..length).step_by(word_size) {
+                let bytes = tx.get(offset..tx.len().min(offset + word_size)).unwrap_or(&[]);
+
+                while controller.SPI_FIFO_STATUS_0.is_set(SPI_FIFO_STATUS_0::TX_FIFO_FULL) {
+                    // Wait for TX FIFO to make room for the next word.
+                }
+
+                controller.SPI_TX_FIFO_0.set(pack_word(bytes) & mask);
+            }
+        }
+
+        // Start the transaction. As all of the above are volatile writes to
+        // Device memory, they are issued before the PIO bit is set.
+        self.start_pio();
+    }
+
+    /// Transmits data over SPI in PIO mode.
+    ///
+    /// The data is fed into TX FIFO in blocks of FIFO depth, so buffers of
+    /// arbitrary length are supported. In packed mode, a partial final FIFO
+    /// word is never padded with bytes beyond the end of `data`. Empty
+    /// buffers are a no-op.
+    fn pio_send_packet(&self, data: &[u8]) -> Result<(), SpiError> {
+        self.pio_transfer(Some(data), None, PACKED_THRESHOLD)
+    }
+
+    /// Receives data over SPI in PIO mode.
+    ///
+    /// The data is drained from RX FIFO in blocks of FIFO depth, so buffers
+    /// of arbitrary length are supported. Surplus bytes of a partial final
+    /// FIFO word are discarded rather than written past the end of `data`.
+    /// Empty buffers are a no-op.
+    fn pio_receive_packet(&self, data: &mut [u8]) -> Result<(), SpiError> {
+        self.pio_transfer(None, Some(as_uninit(data)), PACKED_THRESHOLD)
+    }
+
     /// Initializes the SPI controller.
     ///
+    /// The device clock is enabled and set to [`DEFAULT_FREQUENCY`].
+    /// Enabling the clock goes through the full sequence in the CAR, i.e.
+    /// the controller is put into reset, its clock is ungated and it is
+    /// taken out of reset again, so a cold controller is usable afterwards.
+    ///
     /// NOTE: This method must be called once before an SPI device is usable.
     /// Further, it is required to do the respective [`pinmux`] configuration
-    /// before calling this method.
+    /// before calling this method, e.g. through [`pinmux::configure_spi`].
     ///
+    /// [`DEFAULT_FREQUENCY`]: constant.DEFAULT_FREQUENCY.html
     /// [`pinmux`]: ../pinmux
+    /// [`pinmux::configure_spi`]: ../pinmux/fn.configure_spi.html
     pub fn init(&self) {
         let controller = unsafe { &*self.registers };
 
-        // Set chip-select value to high, 8-bit transfers,
-        // unpacked mode and most significant bit first.
+        // Enable the device clock.
+        self.clock().enable();
+
+        // Note the default clock rate. The controller was just reset, so this can't fail.
+        let _ = self.set_frequency(DEFAULT_FREQUENCY);
+
+        // Set active-low chip-select lines, chip-select value to high,
+        // 8-bit transfers, unpacked mode and most significant bit first.
         controller.SPI_COMMAND_0.modify(
-            SPI_COMMAND_0::CS_SW_HW::SET
+            SPI_COMMAND_0::CS_POL_INACTIVE_0::SET
+            + SPI_COMMAND_0::CS_POL_INACTIVE_1::SET
+            + SPI_COMMAND_0::CS_POL_INACTIVE_2::SET
+            + SPI_COMMAND_0::CS_POL_INACTIVE_3::SET
+            + SPI_COMMAND_0::CS_SW_HW::SET
             + SPI_COMMAND_0::CS_SW_VAL::SET
             + SPI_COMMAND_0::PACKED::CLEAR
+            + SPI_COMMAND_0::EN_LE_BIT::CLEAR
             + SPI_COMMAND_0::BIT_LEN.val(7)
         );
 
-        // Flush the FIFOs.
-        self.flush_fifos();
+        // Flush the FIFOs. A controller that doesn't respond
+        // is reported by the first transfer that is attempted.
+        let _ = self.flush_fifos();
 
-        // Enforce chip-select line 0 for now and drive chip-select low.
-        let cs = 0;
-        controller.SPI_COMMAND_0.modify(
-            SPI_COMMAND_0::CS_SEL.val(cs)
-            + SPI_COMMAND_0::CS_SW_VAL::CLEAR
-        );
+        // Select chip-select line 0 by default. It stays inactive until the first transfer.
+        controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::CS_SEL::Cs0);
     }
 
-    /// Flushes the underlying FIFOs of the UART.
+    /// Initializes the SPI controller with a given configuration.
     ///
-    /// NOTE: This method flushes both, TX FIFO and RX FIFO,
-    /// so be careful when you use it.
-    pub fn flush_fifos(&self) {
+    /// This works like [`Spi::init`], but applies the whole [`SpiConfig`]
+    /// right after the controller was reset, before the FIFOs are flushed
+    /// and before any transfer can be started. The configuration is validated
+    /// up front, so an invalid word length or clock rate is rejected with
+    /// [`SpiError::InvalidArgument`] before the controller is touched, rather
+    /// than leaving it half-configured.
+    ///
+    /// [`Spi::init`]: struct.Spi.html#method.init
+    /// [`SpiConfig`]: struct.SpiConfig.html
+    /// [`SpiError::InvalidArgument`]: enum.SpiError.html#variant.InvalidArgument
+    pub fn init_with(&self, config: SpiConfig) -> Result<(), SpiError> {
+        if config.word_length == 0 || config.word_length > 32 || config.frequency < MIN_FREQUENCY {
+            return Err(SpiError::InvalidArgument);
+        }
+
+        // Reset the controller into its default state.
+        self.init();
+
+        // Apply the configuration while the controller is idle.
+        self.set_frequency(config.frequency)?;
+        self.set_mode(config.mode)?;
+        self.set_bit_order(config.bit_order)?;
+        self.set_word_length(config.word_length)?;
+        self.set_chip_select(config.chip_select)?;
+
+        // Start off with empty FIFOs.
+        self.flush_fifos()
+    }
+
+    /// Sets whether chip-select is managed manually.
+    ///
+    /// By default, every transfer asserts chip-select when it starts and
+    /// deasserts it when it completes. In manual mode, transfers leave
+    /// chip-select alone and it is up to the caller to frame a sequence
+    /// of transfers through [`Spi::assert_cs`] and [`Spi::deassert_cs`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use libtegra::spi::{Spi, SpiError};
+    ///
+    /// fn read_status(spi: &Spi) -> Result<u8, SpiError> {
+    ///     let mut status = [0; 1];
+    ///
+    ///     // Keep chip-select low for the command and the response.
+    ///     spi.set_manual_cs(true);
+    ///     spi.assert_cs();
+    ///     let result = spi.send(&[0x05]).and_then(|_| spi.receive(&mut status));
+    ///     spi.deassert_cs();
+    ///     spi.set_manual_cs(false);
+    ///
+    ///     result.map(|_| status[0])
+    /// }
+    /// ```
+    ///
+    /// [`Spi::assert_cs`]: struct.Spi.html#method.assert_cs
+    /// [`Spi::deassert_cs`]: struct.Spi.html#method.deassert_cs
+    pub fn set_manual_cs(&self, manual: bool) {
+        self.state().manual_cs.store(manual, Ordering::Relaxed);
+    }
+
+    /// Asserts chip-select.
+    ///
+    /// Unless chip-select is managed manually, see [`Spi::set_manual_cs`],
+    /// the next transfer deasserts it again when it completes.
+    ///
+    /// [`Spi::set_manual_cs`]: struct.Spi.html#method.set_manual_cs
+    pub fn assert_cs(&self) {
+        self.drive_cs(true);
+    }
+
+    /// Deasserts chip-select.
+    pub fn deassert_cs(&self) {
+        self.drive_cs(false);
+    }
+
+    /// Powers down the SPI controller.
+    ///
+    /// This is equivalent to [`Spi::disable`] with the device clock being
+    /// gated off. Afterwards, the controller can be brought up again through
+    /// [`Spi::init`]. Calling this method on a controller that is already
+    /// powered down is harmless.
+    ///
+    /// [`Spi::disable`]: struct.Spi.html#method.disable
+    /// [`Spi::init`]: struct.Spi.html#method.init
+    pub fn deinit(&self) {
+        self.disable(true);
+    }
+
+    /// Disables the SPI controller.
+    ///
+    /// The FIFOs are flushed, transmission and reception are disabled and
+    /// chip-select is driven inactive. If `gate_clock` is set, the device
+    /// clock is gated off as well to save power, which resets the controller
+    /// and loses its configuration. Calling this method on a controller
+    /// whose clock is already gated off is harmless.
+    ///
+    /// The controller can be brought up again through [`Spi::enable`].
+    ///
+    /// [`Spi::enable`]: struct.Spi.html#method.enable
+    pub fn disable(&self, gate_clock: bool) {
         let controller = unsafe { &*self.registers };
 
-        // Make sure the controller is in idle state.
-        self.wait_until_ready();
+        // The registers are inaccessible while the clock is gated.
+        if !self.clock().is_enabled() {
+            return;
+        }
 
-        // Issue flush requests for TX FIFO and RX FIFO.
-        controller
-            .SPI_FIFO_STATUS_0
-            .modify(SPI_FIFO_STATUS_0::RX_FIFO_FLUSH::SET + SPI_FIFO_STATUS_0::TX_FIFO_FLUSH::SET);
+        // Flush the FIFOs. The controller is torn down regardless of the outcome.
+        let _ = self.flush_fifos();
 
-        while controller.SPI_FIFO_STATUS_0.is_set(SPI_FIFO_STATUS_0::RX_FIFO_FLUSH)
-            && controller.SPI_FIFO_STATUS_0.is_set(SPI_FIFO_STATUS_0::TX_FIFO_FLUSH)
-        {
-            // Wait for the changes to take effect.
+        // Clear both, the transmit and the receive enable bits.
+        controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::TX_EN::CLEAR + SPI_COMMAND_0::RX_EN::CLEAR);
+
+        // Drive chip-select inactive.
+        self.drive_cs(false);
+
+        // Disable the device clock, if requested.
+        if gate_clock {
+            self.clock().disable();
+            self.state().clock_rate.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Enables the SPI controller after it has been disabled through [`Spi::disable`].
+    ///
+    /// If the device clock has been gated off, it is enabled again and the
+    /// controller is initialized through [`Spi::init`], which restores the
+    /// default configuration. To keep a custom configuration across a power
+    /// cycle, save it through [`Spi::save_state`] before disabling the
+    /// controller and restore it through [`Spi::restore_state`] afterwards.
+    /// Otherwise, the configuration has been retained and nothing is done.
+    ///
+    /// [`Spi::disable`]: struct.Spi.html#method.disable
+    /// [`Spi::init`]: struct.Spi.html#method.init
+    /// [`Spi::save_state`]: struct.Spi.html#method.save_state
+    /// [`Spi::restore_state`]: struct.Spi.html#method.restore_state
+    pub fn enable(&self) {
+        if !self.clock().is_enabled() {
+            self.init();
+        }
+    }
+
+    /// Selects the chip-select line to be asserted during transfers.
+    ///
+    /// The selection is latched by the next transaction, so this must be
+    /// called between transfers. Transfers use the line that was selected
+    /// last. By default, [`ChipSelect::Cs0`] is used.
+    ///
+    /// Lines can also be selected by number, e.g. from board configuration,
+    /// through the `TryFrom<u8>` implementation of [`ChipSelect`], which
+    /// rejects numbers other than 0 through 3:
+    ///
+    /// ```no_run
+    /// use core::convert::TryFrom;
+    /// use libtegra::spi::{ChipSelect, Spi, SpiError};
+    ///
+    /// fn select(spi: &Spi, cs: u8) -> Result<(), SpiError> {
+    ///     spi.set_chip_select(ChipSelect::try_from(cs)?)
+    /// }
+    /// ```
+    ///
+    /// [`ChipSelect::Cs0`]: enum.ChipSelect.html#variant.Cs0
+    /// [`ChipSelect`]: enum.ChipSelect.html
+    pub fn set_chip_select(&self, cs: ChipSelect) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+
+        // Make sure that no transfer is in flight.
+        self.ensure_idle()?;
+
+        // Select the chip-select line.
+        let selection = match cs {
+            ChipSelect::Cs0 => SPI_COMMAND_0::CS_SEL::Cs0,
+            ChipSelect::Cs1 => SPI_COMMAND_0::CS_SEL::Cs1,
+            ChipSelect::Cs2 => SPI_COMMAND_0::CS_SEL::Cs2,
+            ChipSelect::Cs3 => SPI_COMMAND_0::CS_SEL::Cs3,
+        };
+        controller.SPI_COMMAND_0.modify(selection);
+
+        Ok(())
+    }
+
+    /// Sets whether a chip-select line is active-low or active-high.
+    ///
+    /// Transfers, as well as [`Spi::assert_cs`] and [`Spi::deassert_cs`],
+    /// drive the selected line according to its polarity. By default, all
+    /// lines are active-low. The new polarity takes effect the next time
+    /// chip-select is driven, so this must be called between transfers.
+    ///
+    /// [`Spi::assert_cs`]: struct.Spi.html#method.assert_cs
+    /// [`Spi::deassert_cs`]: struct.Spi.html#method.deassert_cs
+    pub fn set_cs_polarity(&self, cs: ChipSelect, active_low: bool) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+
+        // Make sure that no transfer is in flight.
+        self.ensure_idle()?;
+
+        // Set the inactive value of the line, which is high for active-low lines.
+        let polarity = match cs {
+            ChipSelect::Cs0 => SPI_COMMAND_0::CS_POL_INACTIVE_0.val(active_low as u32),
+            ChipSelect::Cs1 => SPI_COMMAND_0::CS_POL_INACTIVE_1.val(active_low as u32),
+            ChipSelect::Cs2 => SPI_COMMAND_0::CS_POL_INACTIVE_2.val(active_low as u32),
+            ChipSelect::Cs3 => SPI_COMMAND_0::CS_POL_INACTIVE_3.val(active_low as u32),
+        };
+        controller.SPI_COMMAND_0.modify(polarity);
+
+        Ok(())
+    }
+
+    /// Transmits data over SPI in PIO mode.
+    ///
+    /// Buffers of arbitrary length are accepted. The transfer is split into
+    /// blocks that fit into TX FIFO, each of which is checked for errors
+    /// before the next one is started. Once the transfer is done, or has
+    /// failed, the controller is left idle with transmission disabled.
+    ///
+    /// The length of the buffer is validated against the word length, see
+    /// [`Spi::set_word_length`], and rejected with [`SpiError::InvalidLength`]
+    /// if it isn't a multiple of the bytes per word. Empty buffers are a no-op.
+    ///
+    /// [`Spi::set_word_length`]: struct.Spi.html#method.set_word_length
+    /// [`SpiError::InvalidLength`]: enum.SpiError.html#variant.InvalidLength
+    pub fn send(&self, data: &[u8]) -> Result<(), SpiError> {
+        self.pio_send_packet(data)
+    }
+
+    /// Receives data over SPI in PIO mode.
+    ///
+    /// Buffers of arbitrary length are accepted. The transfer is split into
+    /// blocks that fit into RX FIFO, each of which is checked for errors
+    /// before the next one is started. Once the transfer is done, or has
+    /// failed, the controller is left idle with reception disabled.
+    ///
+    /// The length of the buffer is validated just like for [`Spi::send`].
+    ///
+    /// NOTE: Unless chip-select is managed manually, it is deasserted after
+    /// every transfer. To read the response to a command within the same
+    /// frame, use [`Spi::write_then_read`] instead of [`Spi::send`] followed
+    /// by this method.
+    ///
+    /// [`Spi::write_then_read`]: struct.Spi.html#method.write_then_read
+    /// [`Spi::send`]: struct.Spi.html#method.send
+    pub fn receive(&self, data: &mut [u8]) -> Result<(), SpiError> {
+        self.pio_receive_packet(data)
+    }
+
+    /// Transmits and receives data over SPI simultaneously in PIO mode.
+    ///
+    /// Each byte of `tx` is clocked out on the same clock edges on which
+    /// a byte is clocked into `rx`, so both buffers must be of the same
+    /// length. Otherwise, [`SpiError::InvalidLength`] is returned.
+    ///
+    /// The transfer is split into blocks of FIFO depth, each of which is
+    /// written to TX FIFO and drained from RX FIFO before the next one is
+    /// started, so neither FIFO overflows and buffers of arbitrary length
+    /// are supported. Transfers of at least [`PACKED_THRESHOLD`] bytes are
+    /// carried out in packed mode.
+    ///
+    /// [`SpiError::InvalidLength`]: enum.SpiError.html#variant.InvalidLength
+    /// [`PACKED_THRESHOLD`]: constant.PACKED_THRESHOLD.html
+    pub fn transfer(&self, tx: &[u8], rx: &mut [u8]) -> Result<(), SpiError> {
+        if tx.len() != rx.len() {
+            return Err(SpiError::InvalidLength);
+        }
+
+        self.pio_transfer(Some(tx), Some(as_uninit(rx)), PACKED_THRESHOLD)
+    }
+
+    /// Transmits and receives a fixed-size array over SPI simultaneously in PIO mode.
+    ///
+    /// This behaves like [`Spi::transfer`], but returns the received data by
+    /// value, so small transfers of a known size can be carried out entirely
+    /// on the stack. As both lengths are the same by construction, no length
+    /// mismatch can occur.
+    ///
+    /// ```no_run
+    /// use libtegra::spi::Spi;
+    ///
+    /// // Read the JEDEC ID of an SPI flash.
+    /// let response = Spi::SPI1.transfer_array(&[0x9F, 0, 0, 0]).unwrap();
+    /// let id = &response[1..];
+    /// ```
+    ///
+    /// [`Spi::transfer`]: struct.Spi.html#method.transfer
+    pub fn transfer_array<const N: usize>(&self, tx: &[u8; N]) -> Result<[u8; N], SpiError> {
+        let mut rx = [0; N];
+        self.pio_transfer(Some(tx), Some(as_uninit(&mut rx)), PACKED_THRESHOLD)?;
+
+        Ok(rx)
+    }
+
+    /// Transmits data over SPI and receives a response within the same frame in PIO mode.
+    ///
+    /// This is the common pattern of sending a command or an address and
+    /// reading back the response, e.g. for SPI flashes and register-based
+    /// sensors. Chip-select is asserted once, `tx` is clocked out while the
+    /// received data is discarded, `rx` is clocked in while dummy data is
+    /// sent, and chip-select is deasserted afterwards. The FIFOs are only
+    /// flushed before the first phase.
+    ///
+    /// The length of both buffers must be a multiple of the bytes per word,
+    /// otherwise [`SpiError::InvalidLength`] is returned.
+    ///
+    /// [`SpiError::InvalidLength`]: enum.SpiError.html#variant.InvalidLength
+    pub fn write_then_read(&self, tx: &[u8], rx: &mut [u8]) -> Result<(), SpiError> {
+        let bytes_per_packet = self.bytes_per_packet();
+
+        if tx.is_empty() && rx.is_empty() {
+            return Ok(());
+        }
+
+        if tx.len() % bytes_per_packet != 0 || rx.len() % bytes_per_packet != 0 {
+            return Err(SpiError::InvalidLength);
+        }
+
+        self.framed(|| {
+            // Send the command, then receive the response.
+            if !tx.is_empty() {
+                self.pio_phase(Some(tx), None, tx.len(), PACKED_THRESHOLD)?;
+            }
+            if !rx.is_empty() {
+                self.pio_phase(None, Some(as_uninit(rx)), rx.len(), PACKED_THRESHOLD)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Transmits data and receives a response over a single, shared data line in PIO mode.
+    ///
+    /// This is the three-wire mode used by some sensors and SD-style
+    /// peripherals. The `BIDIR` bit of `SPI_COMMAND_0` is set for the whole
+    /// frame, so MOSI is driven as output while `write` is clocked out and
+    /// tristated while `read` is clocked in on the same pad. Between both
+    /// phases, the line is left undriven for one clock cycle so the device
+    /// can take it over. Otherwise, this behaves like [`Spi::write_then_read`].
+    ///
+    /// The length of both buffers must be a multiple of the bytes per word,
+    /// otherwise [`SpiError::InvalidLength`] is returned.
+    ///
+    /// NOTE: The device must not drive the line before the write phase is
+    /// over, as both ends would be outputs at the same time otherwise.
+    ///
+    /// [`Spi::write_then_read`]: struct.Spi.html#method.write_then_read
+    /// [`SpiError::InvalidLength`]: enum.SpiError.html#variant.InvalidLength
+    pub fn transfer_half_duplex(&self, write: &[u8], read: &mut [u8]) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+        let bytes_per_packet = self.bytes_per_packet();
+
+        if write.is_empty() && read.is_empty() {
+            return Ok(());
+        }
+
+        if write.len() % bytes_per_packet != 0 || read.len() % bytes_per_packet != 0 {
+            return Err(SpiError::InvalidLength);
         }
+
+        let result = self.framed(|| {
+            // Share MOSI between both directions.
+            controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::BIDIR::SET);
+
+            // Drive the line while the command is sent.
+            if !write.is_empty() {
+                self.pio_phase(Some(write), None, write.len(), PACKED_THRESHOLD)?;
+            }
+
+            // Turn the line around, then receive the response while it is tristated.
+            if !read.is_empty() {
+                usleep(self.cycles_to_us(1));
+
+                self.pio_phase(None, Some(as_uninit(read)), read.len(), PACKED_THRESHOLD)?;
+            }
+
+            Ok(())
+        });
+
+        // Return to regular four-wire operation.
+        controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::BIDIR::CLEAR);
+
+        result
+    }
+
+    /// Transmits and receives data over SPI simultaneously in packed mode.
+    ///
+    /// This behaves like [`Spi::transfer`], except that packed mode is used
+    /// regardless of the length of the buffers. Four bytes are packed into
+    /// every FIFO word, so the FIFOs are accessed a quarter as often. If the
+    /// length of the buffers is not a multiple of 4, the trailing 1 to 3
+    /// bytes are transferred in unpacked mode.
+    ///
+    /// [`Spi::transfer`]: struct.Spi.html#method.transfer
+    pub fn transfer_packed(&self, tx: &[u8], rx: &mut [u8]) -> Result<(), SpiError> {
+        if tx.len() != rx.len() {
+            return Err(SpiError::InvalidLength);
+        }
+
+        self.pio_transfer(Some(tx), Some(as_uninit(rx)), 0)
+    }
+
+    /// Transmits and receives words of the configured length over SPI simultaneously.
+    ///
+    /// This is the word-based counterpart to [`Spi::transfer`]. Every element
+    /// of the buffers holds one word, right-aligned, so a word of `N` bits
+    /// occupies the `N` least significant bits. The remaining bits are ignored
+    /// when transmitting and read as `0` when receiving. The controller maps
+    /// words into FIFO words the same way, thus odd lengths such as 9 or 24
+    /// bits are supported in unpacked mode. Both buffers must be of the same
+    /// length, otherwise [`SpiError::InvalidLength`] is returned.
+    ///
+    /// [`Spi::transfer`]: struct.Spi.html#method.transfer
+    /// [`SpiError::InvalidLength`]: enum.SpiError.html#variant.InvalidLength
+    pub fn transfer_words(&self, tx: &[u32], rx: &mut [u32]) -> Result<(), SpiError> {
+        if tx.len() != rx.len() {
+            return Err(SpiError::InvalidLength);
+        }
+
+        let bytes_per_packet = self.bytes_per_packet();
+        let mut tx_buffer = [0; FIFO_DEPTH * 4];
+        let mut rx_buffer = [0; FIFO_DEPTH * 4];
+
+        for (tx, rx) in tx.chunks(FIFO_DEPTH).zip(rx.chunks_mut(FIFO_DEPTH)) {
+            let length = tx.len() * bytes_per_packet;
+
+            // Lay out the words in little-endian byte order.
+            for (word, bytes) in tx.iter().zip(tx_buffer.chunks_mut(bytes_per_packet)) {
+                bytes.copy_from_slice(&word.to_le_bytes()[..bytes_per_packet]);
+            }
+
+            self.transfer(&tx_buffer[..length], &mut rx_buffer[..length])?;
+
+            // Reassemble the received words.
+            for (word, bytes) in rx.iter_mut().zip(rx_buffer.chunks(bytes_per_packet)) {
+                *word = pack_word(bytes);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Transmits a slice of native words over SPI.
+    ///
+    /// The word length of the controller is set to the width of `W`, i.e.
+    /// 16 or 32 bits, for the duration of the transfer and restored afterwards,
+    /// so callers don't have to pack the words into bytes themselves. Every
+    /// word is transmitted as a whole, in the configured bit order. Transfers
+    /// of at least [`PACKED_THRESHOLD`] bytes are carried out in packed mode.
+    ///
+    /// NOTE: The words are transmitted in chunks of up to 256 bytes, i.e. the
+    /// size of the FIFO. Unless chip-select is managed manually, e.g. through
+    /// [`Spi::select`], it is deasserted between chunks.
+    ///
+    /// ```no_run
+    /// use libtegra::spi::Spi;
+    ///
+    /// // Write two 16-bit registers of a DAC.
+    /// Spi::SPI1.write_words(&[0x3000u16, 0x0FFF]).unwrap();
+    /// ```
+    ///
+    /// [`PACKED_THRESHOLD`]: constant.PACKED_THRESHOLD.html
+    /// [`Spi::select`]: struct.Spi.html#method.select
+    pub fn write_words<W: SpiWord>(&self, data: &[W]) -> Result<(), SpiError> {
+        let guard = self.config_guard();
+        guard.set_word_length(W::BITS)?;
+
+        let bytes_per_word = usize::from(W::BITS / 8);
+        let mut buffer = [0; FIFO_DEPTH * 4];
+
+        for chunk in data.chunks(buffer.len() / bytes_per_word) {
+            let length = chunk.len() * bytes_per_word;
+
+            // Lay out the words in little-endian byte order.
+            for (word, bytes) in chunk.iter().zip(buffer.chunks_mut(bytes_per_word)) {
+                word.write_le(bytes);
+            }
+
+            guard.send(&buffer[..length])?;
+        }
+
+        Ok(())
+    }
+
+    /// Receives native words over SPI into a slice.
+    ///
+    /// This is the receiving counterpart to [`Spi::write_words`], with the
+    /// same handling of the word length.
+    ///
+    /// [`Spi::write_words`]: struct.Spi.html#method.write_words
+    pub fn read_words<W: SpiWord>(&self, data: &mut [W]) -> Result<(), SpiError> {
+        let guard = self.config_guard();
+        guard.set_word_length(W::BITS)?;
+
+        let bytes_per_word = usize::from(W::BITS / 8);
+        let mut buffer = [0; FIFO_DEPTH * 4];
+
+        for chunk in data.chunks_mut(buffer.len() / bytes_per_word) {
+            let length = chunk.len() * bytes_per_word;
+
+            guard.receive(&mut buffer[..length])?;
+
+            // Reassemble the received words.
+            for (word, bytes) in chunk.iter_mut().zip(buffer.chunks(bytes_per_word)) {
+                *word = W::read_le(bytes);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Transmits data over SPI and returns the data received meanwhile.
+    ///
+    /// This is a convenience over [`Spi::transfer`] for small command/response
+    /// exchanges of varying length. The data is received into a scratch buffer
+    /// of [`EXCHANGE_BUFFER_SIZE`] bytes that every controller owns, and the
+    /// part of it that was actually received is returned. If `tx` is longer
+    /// than the scratch buffer, [`SpiError::BufferTooLarge`] is returned.
+    ///
+    /// ```no_run
+    /// use libtegra::spi::Spi;
+    ///
+    /// // Read the status register of an SPI flash.
+    /// let status = Spi::SPI1.exchange(&[0x05, 0]).unwrap()[1];
+    /// ```
+    ///
+    /// # Borrowing
+    ///
+    /// The returned slice borrows the scratch buffer of the controller for as
+    /// long as `self` is borrowed. The next exchange on the same controller,
+    /// through any [`Spi`] of it, overwrites the buffer, so the response must
+    /// be consumed or copied before the controller is exchanged with again.
+    ///
+    /// # Reentrancy
+    ///
+    /// This method is not reentrant. While an exchange is in progress, e.g.
+    /// if it was preempted by an interrupt handler or runs on another core,
+    /// further exchanges on the same controller fail with [`SpiError::Busy`]
+    /// instead of overwriting the scratch buffer under it.
+    ///
+    /// [`Spi::transfer`]: struct.Spi.html#method.transfer
+    /// [`EXCHANGE_BUFFER_SIZE`]: constant.EXCHANGE_BUFFER_SIZE.html
+    /// [`SpiError::BufferTooLarge`]: enum.SpiError.html#variant.BufferTooLarge
+    /// [`Spi`]: struct.Spi.html
+    /// [`SpiError::Busy`]: enum.SpiError.html#variant.Busy
+    pub fn exchange(&self, tx: &[u8]) -> Result<&[u8], SpiError> {
+        let state = self.state();
+
+        if tx.len() > EXCHANGE_BUFFER_SIZE {
+            return Err(SpiError::BufferTooLarge);
+        }
+
+        // Claim the scratch buffer, unless an exchange is in progress.
+        if state.exchanging.swap(true, Ordering::Acquire) {
+            return Err(SpiError::Busy);
+        }
+
+        // SAFETY: The claim grants exclusive access to the scratch buffer for the transfer.
+        let rx = unsafe { &mut (*state.scratch.get())[..tx.len()] };
+        let result = self.transfer(tx, rx);
+
+        // Release the scratch buffer.
+        state.exchanging.store(false, Ordering::Release);
+
+        result?;
+
+        Ok(rx)
+    }
+
+    /// Starts transmitting a single byte over SPI without blocking.
+    ///
+    /// If a transfer is still in flight, [`nb::Error::WouldBlock`] is
+    /// returned. Otherwise, the byte is transmitted as a single word and
+    /// [`Spi::poll_complete`] must be used to wait for its completion.
+    /// If the word length exceeds 8 bits, the byte is zero-extended.
+    ///
+    /// Like every other transfer, this flushes the FIFOs and keeps chip-select
+    /// inactive for the time set through [`Spi::set_min_cs_inactive_us`]
+    /// before the byte is sent.
+    ///
+    /// [`nb::Error::WouldBlock`]: https://docs.rs/nb/0.1/nb/enum.Error.html#variant.WouldBlock
+    /// [`Spi::poll_complete`]: struct.Spi.html#method.poll_complete
+    /// [`Spi::set_min_cs_inactive_us`]: struct.Spi.html#method.set_min_cs_inactive_us
+    pub fn try_send_byte(&self, byte: u8) -> nb::Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+
+        // Make sure that no transfer is in flight.
+        self.ensure_idle().map_err(|_| nb::Error::WouldBlock)?;
+
+        // Open the frame of the transfer.
+        self.open_frame().map_err(nb::Error::Other)?;
+
+        // Set unpacked mode and the transmit enable bit only.
+        controller.SPI_COMMAND_0.modify(
+            SPI_COMMAND_0::PACKED::CLEAR
+            + SPI_COMMAND_0::TX_EN::SET
+            + SPI_COMMAND_0::RX_EN::CLEAR
+        );
+
+        // Set the size of data blocks to be transferred.
+        controller.SPI_DMA_BLK_SIZE_0.set(0);
+
+        // Clear SPI_TRANSFER_STATUS RDY bit.
+        controller.SPI_TRANSFER_STATUS_0.modify(SPI_TRANSFER_STATUS_0::RDY::CLEAR);
+
+        // Load in the byte to write.
+        controller.SPI_TX_FIFO_0.set(u32::from(byte));
+
+        // Start the transaction.
+        self.start_pio();
+
+        Ok(())
+    }
+
+    /// Starts transmitting data over SPI in PIO mode without blocking.
+    ///
+    /// The completion interrupt, i.e. `RDY_INTR_MASK` of `SPI_INTR_MASK_0`,
+    /// is enabled, so the CPU is free to do other work until the controller
+    /// signals the completion of the transfer. [`Spi::poll_complete`] must be
+    /// used to finish the transfer, which disables the interrupt again.
+    ///
+    /// If a transfer is still in flight, [`nb::Error::WouldBlock`] is returned.
+    /// As the transfer is not split into blocks, `data` must fit into TX FIFO.
+    /// Otherwise, [`SpiError::BufferTooLarge`] is returned. The length of
+    /// `data` must be a multiple of the bytes per word, or else
+    /// [`SpiError::InvalidLength`] is returned.
+    ///
+    /// [`Spi::poll_complete`]: struct.Spi.html#method.poll_complete
+    /// [`nb::Error::WouldBlock`]: https://docs.rs/nb/0.1/nb/enum.Error.html#variant.WouldBlock
+    /// [`SpiError::BufferTooLarge`]: enum.SpiError.html#variant.BufferTooLarge
+    /// [`SpiError::InvalidLength`]: enum.SpiError.html#variant.InvalidLength
+    pub fn start_send(&self, data: &[u8]) -> nb::Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+        let length = data.len();
+        let bytes_per_packet = self.bytes_per_packet();
+
+        // Make sure that no transfer is in flight.
+        self.ensure_idle().map_err(|_| nb::Error::WouldBlock)?;
+
+        if length == 0 || length % bytes_per_packet != 0 {
+            return Err(nb::Error::Other(SpiError::InvalidLength));
+        }
+
+        // Use packed mode if the whole transfer can be carried out in it.
+        let packed = self.is_packable() && length >= PACKED_THRESHOLD && length % 4 == 0;
+        let capacity = if packed {
+            FIFO_DEPTH * 4
+        } else {
+            FIFO_DEPTH * bytes_per_packet
+        };
+
+        if length > capacity {
+            return Err(nb::Error::Other(SpiError::BufferTooLarge));
+        }
+
+        // Open the frame of the transfer.
+        self.open_frame().map_err(nb::Error::Other)?;
+
+        // Set the transmit enable bit only.
+        controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::TX_EN::SET + SPI_COMMAND_0::RX_EN::CLEAR);
+
+        // Enable the completion interrupt.
+        controller.SPI_INTR_MASK_0.modify(SPI_INTR_MASK_0::RDY_INTR_MASK::SET);
+
+        // Load in the data and start the transaction.
+        self.pio_start_block(Some(data), length, packed);
+
+        Ok(())
+    }
+
+    /// Checks whether the current transfer has completed, without blocking.
+    ///
+    /// The `RDY` bit of `SPI_TRANSFER_STATUS_0` is checked exactly once.
+    /// If it is not set yet, [`nb::Error::WouldBlock`] is returned. Once
+    /// the transfer has completed, the completion interrupt is disabled,
+    /// transmission and reception are disabled, and the FIFO status is
+    /// checked for errors.
+    ///
+    /// [`nb::Error::WouldBlock`]: https://docs.rs/nb/0.1/nb/enum.Error.html#variant.WouldBlock
+    pub fn poll_complete(&self) -> nb::Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+
+        if !controller.SPI_TRANSFER_STATUS_0.is_set(SPI_TRANSFER_STATUS_0::RDY) {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        // Disable the completion interrupt that was enabled by Spi::start_send.
+        controller.SPI_INTR_MASK_0.modify(SPI_INTR_MASK_0::RDY_INTR_MASK::CLEAR);
+
+        // Clear both, the transmit and the receive enable bits.
+        controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::TX_EN::CLEAR + SPI_COMMAND_0::RX_EN::CLEAR);
+
+        // Close the frame of the transfer.
+        self.close_frame();
+
+        // Check for errors.
+        self.check_fifo_errors().map_err(nb::Error::Other)
+    }
+
+    /// Receives data over SPI in PIO mode into an uninitialized buffer.
+    ///
+    /// This saves callers from zero-initializing large buffers that are
+    /// overwritten by the received data anyway. On success, the whole
+    /// buffer has been filled and is returned as an initialized slice.
+    ///
+    /// # Safety contract
+    ///
+    /// No bytes are claimed to be initialized unless they were actually
+    /// received. If the transfer fails, an error is returned instead and
+    /// the contents of `buf` must still be treated as uninitialized.
+    pub fn read_uninit<'a>(
+        &self,
+        buf: &'a mut [MaybeUninit<u8>],
+    ) -> Result<&'a mut [u8], SpiError> {
+        self.pio_transfer(None, Some(&mut *buf), PACKED_THRESHOLD)?;
+
+        // SAFETY: A successful transfer has written every byte of the buffer.
+        Ok(unsafe { &mut *(buf as *mut [MaybeUninit<u8>] as *mut [u8]) })
+    }
+
+    /// Transmits a buffer over SPI in DMA mode.
+    ///
+    /// The `channel` must be acquired through the APB DMA [`Controller`]
+    /// before it is passed to this method. `data` must be aligned to a
+    /// 4-byte boundary and its length must be a multiple of 4 bytes that
+    /// doesn't exceed 64KiB.
+    ///
+    /// NOTE: The DMA engine reads the buffer from physical memory, so
+    /// it is cleaned from the data cache before the transfer starts.
+    ///
+    /// [`Controller`]: ../apb/dma/struct.Controller.html
+    pub fn dma_send(&self, channel: &Channel, data: &[u8]) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+
+        // Make sure the buffer can be transferred over DMA.
+        Self::check_dma_buffer(data.as_ptr() as usize, data.len())?;
+        if !self.is_packable() {
+            return Err(SpiError::InvalidArgument);
+        }
+
+        self.framed(|| {
+            // Set packed mode.
+            controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::PACKED::SET);
+
+            // Set the size of data blocks to be transferred, in packets.
+            controller.SPI_DMA_BLK_SIZE_0.set((data.len() / self.bytes_per_packet() - 1) as u32);
+
+            // Clear SPI_TRANSFER_STATUS RDY bit.
+            controller.SPI_TRANSFER_STATUS_0.modify(SPI_TRANSFER_STATUS_0::RDY::CLEAR);
+
+            // Set the transmit enable bit.
+            controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::TX_EN::SET);
+
+            // Write the buffer back to memory, where the DMA engine reads it from.
+            clean_dcache_range(data.as_ptr() as usize, data.len());
+
+            // Let the DMA channel feed the buffer into TX FIFO.
+            let result = channel.query(
+                self.dma_request(),
+                data.as_ptr() as u32,
+                &controller.SPI_TX_FIFO_0 as *const _ as u32,
+                (data.len() / 4) as u32,
+            )
+            .map_err(|_| SpiError::DmaError)
+            // Run the transaction.
+            .and_then(|_| self.dma_execute(channel));
+
+            // Clear the transmit enable bit.
+            controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::TX_EN::CLEAR);
+
+            result
+        })?;
+
+        // Check for errors.
+        self.check_fifo_errors()
+    }
+
+    /// Receives data over SPI in DMA mode.
+    ///
+    /// The `channel` must be acquired through the APB DMA [`Controller`]
+    /// before it is passed to this method. `data` must be aligned to a
+    /// 4-byte boundary and its length must be a multiple of 4 bytes that
+    /// doesn't exceed 64KiB.
+    ///
+    /// NOTE: The DMA engine writes the buffer to physical memory, so
+    /// it is invalidated in the data cache once the transfer is done.
+    /// Data that shares a cache line with the start or the end of the
+    /// buffer must not be written by the CPU during the transfer, so the
+    /// buffer should be aligned to [`CACHE_LINE_SIZE`] and a multiple of
+    /// it in length.
+    ///
+    /// [`Controller`]: ../apb/dma/struct.Controller.html
+    /// [`CACHE_LINE_SIZE`]: ../cache/constant.CACHE_LINE_SIZE.html
+    pub fn dma_receive(&self, channel: &Channel, data: &mut [u8]) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+
+        // Make sure the buffer can be transferred over DMA.
+        Self::check_dma_buffer(data.as_ptr() as usize, data.len())?;
+        if !self.is_packable() {
+            return Err(SpiError::InvalidArgument);
+        }
+
+        self.framed(|| {
+            // Set packed mode.
+            controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::PACKED::SET);
+
+            // Set the size of data blocks to be transferred, in packets.
+            controller.SPI_DMA_BLK_SIZE_0.set((data.len() / self.bytes_per_packet() - 1) as u32);
+
+            // Clear SPI_TRANSFER_STATUS RDY bit.
+            controller.SPI_TRANSFER_STATUS_0.modify(SPI_TRANSFER_STATUS_0::RDY::CLEAR);
+
+            // Set the receive enable bit.
+            controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::RX_EN::SET);
+
+            // Make sure no dirty lines get evicted over the buffer during the transfer.
+            clean_invalidate_dcache_range(data.as_ptr() as usize, data.len());
+
+            // Let the DMA channel drain RX FIFO into the buffer.
+            let result = channel.write(
+                self.dma_request(),
+                data.as_mut_ptr() as u32,
+                &controller.SPI_RX_FIFO_0 as *const _ as u32,
+                (data.len() / 4) as u32,
+            )
+            .map_err(|_| SpiError::DmaError)
+            // Run the transaction.
+            .and_then(|_| self.dma_execute(channel));
+
+            // Clear the receive enable bit.
+            controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::RX_EN::CLEAR);
+
+            // Drop lines that were speculatively fetched during the transfer.
+            invalidate_dcache_range(data.as_ptr() as usize, data.len());
+
+            result
+        })?;
+
+        // Check for errors.
+        self.check_fifo_errors()
+    }
+
+    /// Starts a continuous, double-buffered DMA stream.
+    ///
+    /// The controller runs in continuous mode and transfers `buf_a` and
+    /// `buf_b` back to back, in the given [`StreamDirection`], until the
+    /// returned [`DmaStream`] is stopped or dropped. The stream borrows both
+    /// buffers for its whole lifetime, so they can only be accessed through
+    /// [`DmaStream::process_block`] once the DMA engine is done with them.
+    ///
+    /// Both buffers must be aligned to a 4-byte boundary and of the same
+    /// length, which must be a non-zero multiple of 4 bytes that doesn't
+    /// exceed 64KiB. Every completed buffer has to be processed within the
+    /// time it takes to transfer the other one, so the buffers should be sized
+    /// for the latency of the user's refill path, not just the minimum of 4
+    /// bytes.
+    ///
+    /// NOTE: Chip-select stays asserted for the whole stream, unless it is
+    /// managed manually.
+    ///
+    /// # Safety
+    ///
+    /// The stream must be stopped or dropped before the buffers are released,
+    /// i.e. the [`DmaStream`] must not be leaked, e.g. through `mem::forget`.
+    /// Otherwise, the DMA engine keeps accessing the buffers after the borrows
+    /// have ended.
+    ///
+    /// [`StreamDirection`]: enum.StreamDirection.html
+    /// [`DmaStream`]: struct.DmaStream.html
+    /// [`DmaStream::process_block`]: struct.DmaStream.html#method.process_block
+    pub unsafe fn start_stream<'a>(
+        &'a self,
+        channel: &'a Channel,
+        direction: StreamDirection,
+        buf_a: &'a mut [u8],
+        buf_b: &'a mut [u8],
+    ) -> Result<DmaStream<'a>, SpiError> {
+        let controller = &*self.registers;
+
+        // Make sure the buffers can be transferred over DMA.
+        Self::check_dma_buffer(buf_a.as_ptr() as usize, buf_a.len())?;
+        Self::check_dma_buffer(buf_b.as_ptr() as usize, buf_b.len())?;
+        if buf_a.len() != buf_b.len() {
+            return Err(SpiError::InvalidLength);
+        }
+        if !self.is_packable() {
+            return Err(SpiError::InvalidArgument);
+        }
+
+        // Open the frame of the stream.
+        self.open_frame()?;
+
+        // Set packed mode.
+        controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::PACKED::SET);
+
+        // Set the size of data blocks to be transferred, in packets.
+        controller.SPI_DMA_BLK_SIZE_0.set((buf_a.len() / self.bytes_per_packet() - 1) as u32);
+
+        // Clear SPI_TRANSFER_STATUS RDY bit.
+        controller.SPI_TRANSFER_STATUS_0.modify(SPI_TRANSFER_STATUS_0::RDY::CLEAR);
+
+        // Make the buffers coherent with what the DMA engine sees.
+        for buf in [&*buf_a, &*buf_b].iter() {
+            match direction {
+                StreamDirection::Transmit => clean_dcache_range(buf.as_ptr() as usize, buf.len()),
+                StreamDirection::Receive => {
+                    clean_invalidate_dcache_range(buf.as_ptr() as usize, buf.len())
+                }
+            }
+        }
+
+        // Set the enable bit of the direction and prepare the DMA channel.
+        let fifo = match direction {
+            StreamDirection::Transmit => {
+                controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::TX_EN::SET);
+                &controller.SPI_TX_FIFO_0 as *const _ as u32
+            }
+            StreamDirection::Receive => {
+                controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::RX_EN::SET);
+                &controller.SPI_RX_FIFO_0 as *const _ as u32
+            }
+        };
+        let result = channel.stream(
+            self.dma_request(),
+            buf_a.as_mut_ptr() as u32,
+            fifo,
+            (buf_a.len() / 4) as u32,
+            direction == StreamDirection::Transmit,
+        );
+
+        if result.is_err() {
+            controller
+                .SPI_COMMAND_0
+                .modify(SPI_COMMAND_0::TX_EN::CLEAR + SPI_COMMAND_0::RX_EN::CLEAR);
+            self.close_frame();
+
+            return Err(SpiError::DmaError);
+        }
+
+        // Start the DMA channel and queue the second buffer for the next block.
+        channel.start();
+        channel.set_next_address(buf_b.as_mut_ptr() as u32);
+
+        // Enable DMA mode in continuous mode to start the stream.
+        controller.SPI_DMA_CTL_0.modify(
+            SPI_DMA_CTL_0::TX_TRIG::OneWord
+            + SPI_DMA_CTL_0::RX_TRIG::OneWord
+            + SPI_DMA_CTL_0::CONT::SET
+            + SPI_DMA_CTL_0::DMA::SET
+        );
+
+        Ok(DmaStream {
+            spi: self,
+            channel,
+            direction,
+            buffers: [buf_a, buf_b],
+            in_flight: 0,
+            stopped: false,
+        })
+    }
+
+    /// Stops a DMA stream that was started through [`Spi::start_stream`].
+    ///
+    /// The block in flight is cut short and the controller is left idle.
+    /// Afterwards, the FIFO status is checked for errors that occurred
+    /// during the stream.
+    ///
+    /// [`Spi::start_stream`]: struct.Spi.html#method.start_stream
+    fn stop_stream(&self, channel: &Channel) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+
+        // Leave continuous mode and stop the DMA channel.
+        controller
+            .SPI_DMA_CTL_0
+            .modify(SPI_DMA_CTL_0::CONT::CLEAR + SPI_DMA_CTL_0::DMA::CLEAR);
+        channel.finish();
+        channel.take_block_done();
+
+        // Clear the enable bits.
+        controller
+            .SPI_COMMAND_0
+            .modify(SPI_COMMAND_0::TX_EN::CLEAR + SPI_COMMAND_0::RX_EN::CLEAR);
+
+        // Close the frame of the transfer.
+        self.close_frame();
+
+        // Check for errors.
+        self.check_fifo_errors()
+    }
+
+    /// Sets the length of the words to be transferred, in bits.
+    ///
+    /// Valid lengths are in the range `1..=32`, otherwise
+    /// [`SpiError::InvalidArgument`] is returned. By default,
+    /// 8-bit words are transferred.
+    ///
+    /// Words of more than 8 bits are stored in little-endian byte order in
+    /// the transfer buffers, each of them occupying as many whole bytes as
+    /// necessary, e.g. 3 bytes for 24-bit words. Hence the length of the
+    /// buffers must be a multiple of that. Excess bits are ignored when
+    /// transmitting and read as `0` when receiving.
+    ///
+    /// NOTE: Packed mode and DMA transfers are only supported for
+    /// 8-bit, 16-bit and 32-bit words.
+    ///
+    /// [`SpiError::InvalidArgument`]: enum.SpiError.html#variant.InvalidArgument
+    pub fn set_word_length(&self, bits: u8) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+
+        if bits == 0 || bits > 32 {
+            return Err(SpiError::InvalidArgument);
+        }
+
+        // Make sure that no transfer is in flight.
+        self.ensure_idle()?;
+
+        // Set the number of bits per packet.
+        controller.SPI_COMMAND_0.modify(SPI_COMMAND_0::BIT_LEN.val(u32::from(bits) - 1));
+
+        Ok(())
+    }
+
+    /// Gets the length of the words to be transferred, in bits.
+    pub fn word_length(&self) -> u8 {
+        let controller = unsafe { &*self.registers };
+
+        controller.SPI_COMMAND_0.read(SPI_COMMAND_0::BIT_LEN) as u8 + 1
+    }
+
+    /// Gets the number of bytes that are occupied by a word in the transfer buffers.
+    fn bytes_per_packet(&self) -> usize {
+        (self.word_length() as usize + 7) / 8
+    }
+
+    /// Whether the current word length allows for transfers in packed mode.
+    fn is_packable(&self) -> bool {
+        match self.word_length() {
+            8 | 16 | 32 => true,
+            _ => false,
+        }
+    }
+
+    /// Gets the number of bytes that are moved by every access to the FIFOs.
+    ///
+    /// This is computed from the current `PACKED` and `BIT_LEN` settings.
+    /// In unpacked mode, every FIFO word holds a single packet, e.g. 1 byte
+    /// for 8-bit transfers or 2 bytes for 16-bit transfers. In packed mode,
+    /// as many packets as fit are packed into a FIFO word, e.g. 4 bytes for
+    /// 8-bit transfers.
+    pub fn bytes_per_fifo_word(&self) -> u8 {
+        let controller = unsafe { &*self.registers };
+
+        let bytes_per_packet = self.bytes_per_packet() as u8;
+
+        if controller.SPI_COMMAND_0.is_set(SPI_COMMAND_0::PACKED) {
+            4 / bytes_per_packet * bytes_per_packet
+        } else {
+            bytes_per_packet
+        }
+    }
+
+    /// Gets the number of FIFO words that are waiting to be read from the RX FIFO.
+    pub fn rx_fifo_count(&self) -> u8 {
+        let controller = unsafe { &*self.registers };
+
+        controller.SPI_FIFO_STATUS_0.read(SPI_FIFO_STATUS_0::RX_FIFO_FULL_COUNT) as u8
+    }
+
+    /// Gets the number of FIFO words that are waiting to be shifted out of the TX FIFO.
+    ///
+    /// NOTE: The hardware reports the number of empty TX FIFO entries,
+    /// so this is derived from the FIFO depth of 64 words.
+    pub fn tx_fifo_count(&self) -> u8 {
+        let controller = unsafe { &*self.registers };
+
+        let empty = controller.SPI_FIFO_STATUS_0.read(SPI_FIFO_STATUS_0::TX_FIFO_EMPTY_COUNT) as usize;
+
+        FIFO_DEPTH.saturating_sub(empty) as u8
+    }
+
+    /// Gets the number of FIFO words that can be written to the TX FIFO without overflowing it.
+    ///
+    /// This allows for writing batches of data up to the free space of the
+    /// TX FIFO, instead of polling for every single word.
+    pub fn tx_fifo_free(&self) -> u8 {
+        let controller = unsafe { &*self.registers };
+
+        controller.SPI_FIFO_STATUS_0.read(SPI_FIFO_STATUS_0::TX_FIFO_EMPTY_COUNT) as u8
+    }
+
+    /// Whether a transfer has been started and did not complete yet.
+    ///
+    /// This reads the status of the controller once and returns immediately.
+    /// Completion is signaled by the `RDY` bit of `SPI_TRANSFER_STATUS_0`.
+    /// It is the non-blocking counterpart of [`Spi::wait_until_ready_timeout`]
+    /// and allows for scheduling other work while a transfer is in flight.
+    ///
+    /// [`Spi::wait_until_ready_timeout`]: struct.Spi.html#method.wait_until_ready_timeout
+    pub fn is_busy(&self) -> bool {
+        let controller = unsafe { &*self.registers };
+
+        let started = controller.SPI_COMMAND_0.is_set(SPI_COMMAND_0::PIO)
+            || controller.SPI_DMA_CTL_0.is_set(SPI_DMA_CTL_0::DMA);
+
+        started && !controller.SPI_TRANSFER_STATUS_0.is_set(SPI_TRANSFER_STATUS_0::RDY)
+    }
+
+    /// Sets the time in microseconds that a PIO transfer block or a FIFO flush may take.
+    ///
+    /// Transfers that time out fail with [`SpiError::Timeout`] instead of
+    /// hanging forever, e.g. due to a misconfigured pinmux or an unpowered
+    /// device. By default, [`DEFAULT_TIMEOUT_US`] is used.
+    ///
+    /// [`SpiError::Timeout`]: enum.SpiError.html#variant.Timeout
+    /// [`DEFAULT_TIMEOUT_US`]: constant.DEFAULT_TIMEOUT_US.html
+    pub fn set_timeout(&self, us: u32) {
+        self.state().timeout_us.store(us, Ordering::Relaxed);
+    }
+
+    /// Sets the clock rate of the controller, in Hz.
+    ///
+    /// The rate is derived from `PLLP_OUT0` through the divider in the
+    /// `CLK_SOURCE_SPIx` register of CAR. Rates below about 3.17 MHz, which
+    /// `PLLP_OUT0` can't be divided down to, are derived from `CLK_M` instead,
+    /// e.g. for 1 MHz sensors. As the divider only supports discrete steps,
+    /// the closest rate that doesn't exceed `hz` is chosen and returned.
+    /// Rates above [`MAX_FREQUENCY`] are clamped to it and rates that can't
+    /// be reached from `CLK_M` either, including `0`, are rejected with
+    /// [`SpiError::InvalidArgument`]. [`MIN_FREQUENCY`] is always reachable.
+    ///
+    /// [`MAX_FREQUENCY`]: constant.MAX_FREQUENCY.html
+    /// [`MIN_FREQUENCY`]: constant.MIN_FREQUENCY.html
+    /// [`SpiError::InvalidArgument`]: enum.SpiError.html#variant.InvalidArgument
+    pub fn set_frequency(&self, hz: u32) -> Result<u32, SpiError> {
+        // Make sure that no transfer is in flight.
+        self.ensure_idle()?;
+
+        // Pick PLLP_OUT0 as the clock source, unless the rate is too low for it.
+        let hz = hz.min(MAX_FREQUENCY);
+        let (source, (divisor, rate)) = match clock_divisor(PLLP_OUT0_RATE, hz) {
+            Some(divisor) => (CLK_SOURCE_PLLP_OUT0, divisor),
+            None => (
+                CLK_SOURCE_CLK_M,
+                clock_divisor(clk_m_rate(), hz).ok_or(SpiError::InvalidArgument)?,
+            ),
+        };
+
+        // Program the clock source and divider.
+        self.clock().set_source(source, divisor);
+        self.state().clock_rate.store(rate, Ordering::Relaxed);
+
+        Ok(rate)
+    }
+
+    /// Gets the clock rate of the controller that was set through [`Spi::set_frequency`], in Hz.
+    ///
+    /// If the rate was never set, the controller runs at whatever rate was
+    /// configured before, e.g. by the bootloader, and `None` is returned.
+    ///
+    /// [`Spi::set_frequency`]: struct.Spi.html#method.set_frequency
+    pub fn frequency(&self) -> Option<u32> {
+        match self.state().clock_rate.load(Ordering::Relaxed) {
+            0 => None,
+            rate => Some(rate),
+        }
+    }
+
+    /// Sets the SPI mode, i.e. the clock polarity and phase, of the controller.
+    ///
+    /// Along with the clock mode, the data line is configured to idle at
+    /// the same level as the clock, i.e. low for [`SpiMode::Mode0`] and
+    /// [`SpiMode::Mode1`] and high for [`SpiMode::Mode2`] and [`SpiMode::Mode3`].
+    ///
+    /// The mode persists across transfers until it is changed again, so this
+    /// must be called between transfers, e.g. after [`Spi::init`].
+    ///
+    /// [`SpiMode::Mode0`]: enum.SpiMode.html#variant.Mode0
+    /// [`SpiMode::Mode1`]: enum.SpiMode.html#variant.Mode1
+    /// [`SpiMode::Mode2`]: enum.SpiMode.html#variant.Mode2
+    /// [`SpiMode::Mode3`]: enum.SpiMode.html#variant.Mode3
+    /// [`Spi::init`]: struct.Spi.html#method.init
+    pub fn set_mode(&self, mode: SpiMode) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+
+        // Make sure that no transfer is in flight.
+        self.ensure_idle()?;
+
+        // Set the clock mode and the matching idle state of the data line.
+        let mode = match mode {
+            SpiMode::Mode0 => SPI_COMMAND_0::MODE::Mode0 + SPI_COMMAND_0::IDLE_SDA::DriveLow,
+            SpiMode::Mode1 => SPI_COMMAND_0::MODE::Mode1 + SPI_COMMAND_0::IDLE_SDA::DriveLow,
+            SpiMode::Mode2 => SPI_COMMAND_0::MODE::Mode2 + SPI_COMMAND_0::IDLE_SDA::DriveHigh,
+            SpiMode::Mode3 => SPI_COMMAND_0::MODE::Mode3 + SPI_COMMAND_0::IDLE_SDA::DriveHigh,
+        };
+        controller.SPI_COMMAND_0.modify(mode);
+
+        Ok(())
+    }
+
+    /// Sets the minimum time in microseconds for chip-select to stay inactive between transfers.
+    ///
+    /// If the clock rate of the controller is known and the time can be
+    /// expressed in up to 31 clock cycles, the hardware is programmed to
+    /// insert the respective inactive cycles between packets on the selected
+    /// chip-select line. Otherwise, the time is enforced in software, by
+    /// delaying the start of a transfer until chip-select has been inactive
+    /// for long enough since the previous one.
+    ///
+    /// As the hardware cycles are programmed for the currently selected
+    /// chip-select line, this must be called after [`Spi::set_chip_select`].
+    ///
+    /// [`Spi::set_chip_select`]: struct.Spi.html#method.set_chip_select
+    pub fn set_min_cs_inactive_us(&self, us: u32) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+
+        // Make sure that no transfer is in flight.
+        self.ensure_idle()?;
+
+        // Convert the time into clock cycles, if possible.
+        let rate = u64::from(self.state().clock_rate.load(Ordering::Relaxed));
+        let cycles = (u64::from(us) * rate + 999_999) / 1_000_000;
+        let (cycles, software_us) = if rate != 0 && cycles <= 31 {
+            (cycles as u32, 0)
+        } else {
+            (0, us)
+        };
+
+        // Program the inactive cycles for the selected chip-select line.
+        let inactive_cycles = match controller.SPI_COMMAND_0.read(SPI_COMMAND_0::CS_SEL) {
+            0 => SPI_TIMING_REG2_0::CYCLES_BETWEEN_PACKETS_0.val(cycles),
+            1 => SPI_TIMING_REG2_0::CYCLES_BETWEEN_PACKETS_1.val(cycles),
+            2 => SPI_TIMING_REG2_0::CYCLES_BETWEEN_PACKETS_2.val(cycles),
+            _ => SPI_TIMING_REG2_0::CYCLES_BETWEEN_PACKETS_3.val(cycles),
+        };
+        controller.SPI_TIMING_REG2_0.modify(inactive_cycles);
+
+        // Enforce the remaining time in software.
+        self.state().min_cs_inactive_us.store(software_us, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// Sets the chip-select timing of the selected chip-select line.
+    ///
+    /// Refer to [`CsTiming`] for the register fields that every parameter
+    /// maps to. Parameters that exceed the width of their field are rejected
+    /// with [`SpiError::InvalidArgument`]. Chip-select is driven in software,
+    /// so transfers additionally wait for the setup and hold times before
+    /// the first and after the last clock edge respectively.
+    ///
+    /// As the timing is programmed for the currently selected chip-select
+    /// line, this must be called after [`Spi::set_chip_select`]. Note that
+    /// this overrides the inactive cycles that [`Spi::set_min_cs_inactive_us`]
+    /// programmed into the hardware.
+    ///
+    /// [`CsTiming`]: struct.CsTiming.html
+    /// [`SpiError::InvalidArgument`]: enum.SpiError.html#variant.InvalidArgument
+    /// [`Spi::set_chip_select`]: struct.Spi.html#method.set_chip_select
+    /// [`Spi::set_min_cs_inactive_us`]: struct.Spi.html#method.set_min_cs_inactive_us
+    pub fn set_cs_timing(&self, timing: CsTiming) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+
+        if timing.setup_cycles > 0xF || timing.hold_cycles > 0xF || timing.inactive_cycles > 0x1F {
+            return Err(SpiError::InvalidArgument);
+        }
+
+        // Make sure that no transfer is in flight.
+        self.ensure_idle()?;
+
+        let setup = u32::from(timing.setup_cycles);
+        let hold = u32::from(timing.hold_cycles);
+        let inactive = u32::from(timing.inactive_cycles);
+
+        // Program the timing for the selected chip-select line.
+        let (times, inactive_cycles) = match controller.SPI_COMMAND_0.read(SPI_COMMAND_0::CS_SEL) {
+            0 => (
+                SPI_TIMING_REG1_0::CS_SETUP_TIME_0.val(setup) + SPI_TIMING_REG1_0::CS_HOLD_TIME_0.val(hold),
+                SPI_TIMING_REG2_0::CYCLES_BETWEEN_PACKETS_0.val(inactive),
+            ),
+            1 => (
+                SPI_TIMING_REG1_0::CS_SETUP_TIME_1.val(setup) + SPI_TIMING_REG1_0::CS_HOLD_TIME_1.val(hold),
+                SPI_TIMING_REG2_0::CYCLES_BETWEEN_PACKETS_1.val(inactive),
+            ),
+            2 => (
+                SPI_TIMING_REG1_0::CS_SETUP_TIME_2.val(setup) + SPI_TIMING_REG1_0::CS_HOLD_TIME_2.val(hold),
+                SPI_TIMING_REG2_0::CYCLES_BETWEEN_PACKETS_2.val(inactive),
+            ),
+            _ => (
+                SPI_TIMING_REG1_0::CS_SETUP_TIME_3.val(setup) + SPI_TIMING_REG1_0::CS_HOLD_TIME_3.val(hold),
+                SPI_TIMING_REG2_0::CYCLES_BETWEEN_PACKETS_3.val(inactive),
+            ),
+        };
+        controller.SPI_TIMING_REG1_0.modify(times);
+        controller.SPI_TIMING_REG2_0.modify(inactive_cycles);
+
+        Ok(())
+    }
+
+    /// Gets the chip-select timing of the selected chip-select line.
+    pub fn cs_timing(&self) -> CsTiming {
+        let controller = unsafe { &*self.registers };
+
+        let timing1 = &controller.SPI_TIMING_REG1_0;
+        let timing2 = &controller.SPI_TIMING_REG2_0;
+        let (setup, hold, inactive) = match controller.SPI_COMMAND_0.read(SPI_COMMAND_0::CS_SEL) {
+            0 => (
+                timing1.read(SPI_TIMING_REG1_0::CS_SETUP_TIME_0),
+                timing1.read(SPI_TIMING_REG1_0::CS_HOLD_TIME_0),
+                timing2.read(SPI_TIMING_REG2_0::CYCLES_BETWEEN_PACKETS_0),
+            ),
+            1 => (
+                timing1.read(SPI_TIMING_REG1_0::CS_SETUP_TIME_1),
+                timing1.read(SPI_TIMING_REG1_0::CS_HOLD_TIME_1),
+                timing2.read(SPI_TIMING_REG2_0::CYCLES_BETWEEN_PACKETS_1),
+            ),
+            2 => (
+                timing1.read(SPI_TIMING_REG1_0::CS_SETUP_TIME_2),
+                timing1.read(SPI_TIMING_REG1_0::CS_HOLD_TIME_2),
+                timing2.read(SPI_TIMING_REG2_0::CYCLES_BETWEEN_PACKETS_2),
+            ),
+            _ => (
+                timing1.read(SPI_TIMING_REG1_0::CS_SETUP_TIME_3),
+                timing1.read(SPI_TIMING_REG1_0::CS_HOLD_TIME_3),
+                timing2.read(SPI_TIMING_REG2_0::CYCLES_BETWEEN_PACKETS_3),
+            ),
+        };
+
+        CsTiming {
+            setup_cycles: setup as u8,
+            hold_cycles: hold as u8,
+            inactive_cycles: inactive as u8,
+        }
+    }
+
+    /// Enables or disables an interrupt source.
+    fn set_interrupt(&self, interrupt: SpiInterrupt, enabled: bool) {
+        let controller = unsafe { &*self.registers };
+        let value = enabled as u32;
+
+        match interrupt {
+            SpiInterrupt::TransferDone => controller
+                .SPI_INTR_MASK_0
+                .modify(SPI_INTR_MASK_0::RDY_INTR_MASK.val(value)),
+            SpiInterrupt::TxFifoTrigger => controller.SPI_DMA_CTL_0.modify(SPI_DMA_CTL_0::IE_TX.val(value)),
+            SpiInterrupt::RxFifoTrigger => controller.SPI_DMA_CTL_0.modify(SPI_DMA_CTL_0::IE_RX.val(value)),
+            SpiInterrupt::TxOverflow => controller
+                .SPI_INTR_MASK_0
+                .modify(SPI_INTR_MASK_0::TX_FIFO_OVF_INTR_MASK.val(value)),
+            SpiInterrupt::TxUnderrun => controller
+                .SPI_INTR_MASK_0
+                .modify(SPI_INTR_MASK_0::TX_FIFO_UNF_INTR_MASK.val(value)),
+            SpiInterrupt::RxOverflow => controller
+                .SPI_INTR_MASK_0
+                .modify(SPI_INTR_MASK_0::RX_FIFO_OVF_INTR_MASK.val(value)),
+            SpiInterrupt::RxUnderrun => controller
+                .SPI_INTR_MASK_0
+                .modify(SPI_INTR_MASK_0::RX_FIFO_UNF_INTR_MASK.val(value)),
+        }
+    }
+
+    /// Enables an interrupt source of the controller.
+    ///
+    /// Routing the interrupt of the controller to a handler is up to the
+    /// user. The handler must acknowledge the interrupt, see
+    /// [`Spi::clear_interrupt`], or disable its source, otherwise it fires
+    /// again right away.
+    ///
+    /// NOTE: [`Spi::start_send`] and [`Spi::poll_complete`] enable and
+    /// disable [`SpiInterrupt::TransferDone`] on their own.
+    ///
+    /// [`Spi::clear_interrupt`]: struct.Spi.html#method.clear_interrupt
+    /// [`Spi::start_send`]: struct.Spi.html#method.start_send
+    /// [`Spi::poll_complete`]: struct.Spi.html#method.poll_complete
+    /// [`SpiInterrupt::TransferDone`]: enum.SpiInterrupt.html#variant.TransferDone
+    pub fn enable_interrupt(&self, interrupt: SpiInterrupt) {
+        self.set_interrupt(interrupt, true);
+    }
+
+    /// Disables an interrupt source of the controller.
+    pub fn disable_interrupt(&self, interrupt: SpiInterrupt) {
+        self.set_interrupt(interrupt, false);
+    }
+
+    /// Acknowledges the pending interrupts of the controller.
+    ///
+    /// The interrupt line stays asserted as long as any enabled source is
+    /// pending, so the following status bits are cleared:
+    ///
+    /// - `RDY` of `SPI_TRANSFER_STATUS_0`, for [`SpiInterrupt::TransferDone`].
+    /// - `ERR`, `TX_FIFO_OVF`, `TX_FIFO_UNR`, `RX_FIFO_OVF` and `RX_FIFO_UNR`
+    ///   of `SPI_FIFO_STATUS_0`, for the FIFO error sources.
+    ///
+    /// The FIFO trigger sources have no status bit. They are pending for as
+    /// long as the FIFO level satisfies the trigger level, so the handler
+    /// must fill TX FIFO or drain RX FIFO, or disable them.
+    ///
+    /// NOTE: This discards the completion and the error status of the
+    /// current transfer. For transfers started through [`Spi::start_send`],
+    /// call [`Spi::poll_complete`] from the handler instead, which disables
+    /// the completion interrupt and clears the FIFO error bits on its own.
+    ///
+    /// [`SpiInterrupt::TransferDone`]: enum.SpiInterrupt.html#variant.TransferDone
+    /// [`Spi::start_send`]: struct.Spi.html#method.start_send
+    /// [`Spi::poll_complete`]: struct.Spi.html#method.poll_complete
+    pub fn clear_interrupt(&self) {
+        let controller = unsafe { &*self.registers };
+
+        // Clear SPI_TRANSFER_STATUS RDY bit.
+        controller.SPI_TRANSFER_STATUS_0.modify(SPI_TRANSFER_STATUS_0::RDY::CLEAR);
+
+        // Clear the FIFO error bits.
+        self.clear_fifo_status();
+    }
+
+    /// Sets the order in which the bits of a word are transferred.
+    ///
+    /// The controller reverses the bits of every word on its own, so the
+    /// layout of the transfer buffers is the same for both orders. By
+    /// default, [`BitOrder::MsbFirst`] is used.
+    ///
+    /// [`BitOrder::MsbFirst`]: enum.BitOrder.html#variant.MsbFirst
+    pub fn set_bit_order(&self, order: BitOrder) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+
+        // Make sure that no transfer is in flight.
+        self.ensure_idle()?;
+
+        // Set the bit order.
+        let order = match order {
+            BitOrder::MsbFirst => SPI_COMMAND_0::EN_LE_BIT::CLEAR,
+            BitOrder::LsbFirst => SPI_COMMAND_0::EN_LE_BIT::SET,
+        };
+        controller.SPI_COMMAND_0.modify(order);
+
+        Ok(())
+    }
+
+    /// Gets the order in which the bits of a word are currently transferred.
+    pub fn bit_order(&self) -> BitOrder {
+        let controller = unsafe { &*self.registers };
+
+        if controller.SPI_COMMAND_0.is_set(SPI_COMMAND_0::EN_LE_BIT) {
+            BitOrder::LsbFirst
+        } else {
+            BitOrder::MsbFirst
+        }
+    }
+
+    /// Captures the current configuration of the controller.
+    ///
+    /// The transfer control bits, i.e. `PIO`, `TX_EN` and `RX_EN`, are not
+    /// part of the configuration and thus not captured.
+    pub fn save_state(&self) -> SpiState {
+        let controller = unsafe { &*self.registers };
+
+        let transfer_bits = SPI_COMMAND_0::PIO::SET + SPI_COMMAND_0::TX_EN::SET + SPI_COMMAND_0::RX_EN::SET;
+
+        SpiState {
+            command: controller.SPI_COMMAND_0.get() & !transfer_bits.value,
+            command2: controller.SPI_COMMAND2_0.get(),
+            timing1: controller.SPI_TIMING_REG1_0.get(),
+            timing2: controller.SPI_TIMING_REG2_0.get(),
+        }
+    }
+
+    /// Restores a configuration that was previously captured through [`Spi::save_state`].
+    ///
+    /// [`Spi::save_state`]: struct.Spi.html#method.save_state
+    pub fn restore_state(&self, state: &SpiState) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+
+        // Make sure that no transfer is in flight.
+        self.ensure_idle()?;
+
+        // Restore the register values.
+        controller.SPI_TIMING_REG1_0.set(state.timing1);
+        controller.SPI_TIMING_REG2_0.set(state.timing2);
+        controller.SPI_COMMAND2_0.set(state.command2);
+        controller.SPI_COMMAND_0.set(state.command);
+
+        Ok(())
+    }
+
+    /// Captures the chip-select configuration of the controller.
+    ///
+    /// The captured state consists of the selected chip-select line, the
+    /// inactive polarities of all lines, the SPI mode, and whether the
+    /// chip-select is driven by software along with its current level.
+    ///
+    /// This is meant to be used across power transitions, to restore the
+    /// exact chip-select configuration after a low-power cycle.
+    pub fn save_cs_state(&self) -> CsState {
+        let controller = unsafe { &*self.registers };
+
+        CsState {
+            command: controller.SPI_COMMAND_0.get() & Self::cs_state_mask(),
+        }
+    }
+
+    /// Restores a chip-select configuration that was previously captured
+    /// through [`Spi::save_cs_state`].
+    ///
+    /// NOTE: Only the chip-select configuration is restored. The contents
+    /// of the FIFOs are lost during a power transition and the remaining
+    /// configuration must be restored separately, e.g. through
+    /// [`Spi::restore_state`].
+    ///
+    /// [`Spi::save_cs_state`]: struct.Spi.html#method.save_cs_state
+    /// [`Spi::restore_state`]: struct.Spi.html#method.restore_state
+    pub fn restore_cs_state(&self, state: &CsState) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+
+        // Make sure that no transfer is in flight.
+        self.ensure_idle()?;
+
+        // Replace the chip-select bits.
+        let command = controller.SPI_COMMAND_0.get() & !Self::cs_state_mask();
+        controller.SPI_COMMAND_0.set(command | state.command);
+
+        Ok(())
+    }
+
+    /// Gets the mask of the `SPI_COMMAND_0` bits that make up a [`CsState`].
+    ///
+    /// [`CsState`]: struct.CsState.html
+    fn cs_state_mask() -> u32 {
+        (SPI_COMMAND_0::MODE::Mode3
+            + SPI_COMMAND_0::IDLE_SDA::ExternalPullHigh
+            + SPI_COMMAND_0::CS_SEL::Cs3
+            + SPI_COMMAND_0::CS_POL_INACTIVE_3::SET
+            + SPI_COMMAND_0::CS_POL_INACTIVE_2::SET
+            + SPI_COMMAND_0::CS_POL_INACTIVE_1::SET
+            + SPI_COMMAND_0::CS_POL_INACTIVE_0::SET
+            + SPI_COMMAND_0::CS_SW_HW::SET
+            + SPI_COMMAND_0::CS_SW_VAL::SET)
+            .value
+    }
+
+    /// Creates a guard that restores the current configuration when it goes out of scope.
+    ///
+    /// The guard dereferences to the controller, so it can be reconfigured
+    /// and used for transfers temporarily. The captured configuration is
+    /// restored on drop, also on early returns and panics.
+    pub fn config_guard(&self) -> ConfigGuard<'_> {
+        ConfigGuard {
+            spi: self,
+            state: self.save_state(),
+        }
+    }
+
+    /// Verifies that the controller transfers data correctly through a loopback.
+    ///
+    /// A known byte pattern is transferred with 8-bit words, most significant
+    /// bit first, and the data read back must match it exactly. Otherwise,
+    /// [`SpiError::LoopbackMismatch`] is returned. The previous configuration
+    /// is restored afterwards.
+    ///
+    /// NOTE: The controller has no internal loopback mode, so MOSI must be
+    /// tied to MISO for this test, e.g. through a jumper during board bring-up.
+    ///
+    /// [`SpiError::LoopbackMismatch`]: enum.SpiError.html#variant.LoopbackMismatch
+    pub fn self_test(&self) -> Result<(), SpiError> {
+        let guard = self.config_guard();
+        let mut readback = [0; SELF_TEST_PATTERN.len()];
+
+        // Use a configuration in which the data is read back unaltered.
+        guard.set_word_length(8)?;
+        guard.set_bit_order(BitOrder::MsbFirst)?;
+
+        guard.transfer(&SELF_TEST_PATTERN, &mut readback)?;
+
+        if readback != SELF_TEST_PATTERN {
+            return Err(SpiError::LoopbackMismatch);
+        }
+
+        Ok(())
+    }
+
+    /// Asserts chip-select and creates a guard that deasserts it when it goes out of scope.
+    ///
+    /// While the guard is alive, chip-select is managed manually, so it
+    /// stays asserted across several transfers, which can be issued through
+    /// the guard as it dereferences to the controller. On drop, also on early
+    /// returns and panics, chip-select is deasserted and the previous manual
+    /// management setting is restored.
+    ///
+    /// ```no_run
+    /// use libtegra::spi::{Spi, SpiError};
+    ///
+    /// fn read_id(spi: &Spi) -> Result<[u8; 3], SpiError> {
+    ///     let mut id = [0; 3];
+    ///
+    ///     // Keep chip-select low for the command and the response.
+    ///     let cs = spi.select();
+    ///     cs.send(&[0x9F])?;
+    ///     cs.receive(&mut id)?;
+    ///
+    ///     Ok(id)
+    /// }
+    /// ```
+    ///
+    /// NOTE: Guards must not be nested, as the inner guard deasserts
+    /// chip-select when it is dropped.
+    pub fn select(&self) -> CsGuard<'_> {
+        let manual = self.state().manual_cs.swap(true, Ordering::Relaxed);
+
+        // Keep chip-select inactive for the minimum time.
+        self.wait_min_cs_inactive();
+
+        // Assert chip-select.
+        self.drive_cs(true);
+
+        CsGuard { spi: self, manual }
+    }
+
+    /// Gets the time in microseconds that has passed since the last transfer activity.
+    ///
+    /// The timestamp is taken when a transfer starts and again when it
+    /// completes, so this effectively measures for how long the controller
+    /// has been idle. It can be used to decide whether an unused controller
+    /// should be clock-gated.
+    ///
+    /// NOTE: The measurement is based on the 32-bit microsecond counter,
+    /// so idle periods longer than ~71 minutes wrap around.
+    pub fn idle_duration_us(&self) -> u64 {
+        let last_activity = self.state().last_activity.load(Ordering::Relaxed);
+
+        u64::from(get_microseconds().wrapping_sub(last_activity))
+    }
+
+    /// Transfers a buffer over SPI in DMA mode.
+    ///
+    /// This is the common entry point to [`Spi::dma_send`] and
+    /// [`Spi::dma_receive`]. Exactly one of `tx` and `rx` must be
+    /// provided, as a single DMA channel can only serve one direction.
+    /// Otherwise, [`SpiError::InvalidArgument`] is returned.
+    ///
+    /// Buffers must be aligned to a 4-byte boundary and their length
+    /// must be a multiple of 4 bytes, up to 64KiB.
+    ///
+    /// # Cache maintenance
+    ///
+    /// The APB DMA engine accesses physical memory directly and is not
+    /// coherent with the CPU data cache. `tx` is therefore cleaned from
+    /// the data cache before the transfer and `rx` is invalidated after
+    /// it, see the [`cache`] module. As invalidation works on whole cache
+    /// lines, `rx` should be aligned to and padded to [`CACHE_LINE_SIZE`].
+    ///
+    /// [`cache`]: ../cache/index.html
+    /// [`CACHE_LINE_SIZE`]: ../cache/constant.CACHE_LINE_SIZE.html
+    /// [`Spi::dma_send`]: struct.Spi.html#method.dma_send
+    /// [`Spi::dma_receive`]: struct.Spi.html#method.dma_receive
+    /// [`SpiError::InvalidArgument`]: enum.SpiError.html#variant.InvalidArgument
+    pub fn transfer_dma(
+        &self,
+        channel: &Channel,
+        tx: Option<&[u8]>,
+        rx: Option<&mut [u8]>,
+    ) -> Result<(), SpiError> {
+        match (tx, rx) {
+            (Some(tx), None) => self.dma_send(channel, tx),
+            (None, Some(rx)) => self.dma_receive(channel, rx),
+            _ => Err(SpiError::InvalidArgument),
+        }
+    }
+
+    /// Waits for the SPI Controller to complete all transactions within `timeout_us` microseconds.
+    ///
+    /// If the `RDY` bit of `SPI_TRANSFER_STATUS_0` isn't set in time,
+    /// [`SpiError::Timeout`] is returned instead of spinning forever.
+    ///
+    /// [`SpiError::Timeout`]: enum.SpiError.html#variant.Timeout
+    pub fn wait_until_ready_timeout(&self, timeout_us: u32) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+
+        wait_for_field(
+            &controller.SPI_TRANSFER_STATUS_0,
+            SPI_TRANSFER_STATUS_0::RDY,
+            1,
+            timeout_us,
+        )?;
+
+        Ok(())
+    }
+
+    /// Flushes the underlying FIFOs of the SPI.
+    ///
+    /// This only returns successfully once both flushes have completed.
+    /// Both, waiting for the controller to become idle and waiting for
+    /// the flushes to complete, are bounded by the configured timeout,
+    /// see [`Spi::set_timeout`].
+    ///
+    /// NOTE: This method flushes both, TX FIFO and RX FIFO,
+    /// so be careful when you use it.
+    ///
+    /// [`Spi::set_timeout`]: struct.Spi.html#method.set_timeout
+    pub fn flush_fifos(&self) -> Result<(), SpiError> {
+        let controller = unsafe { &*self.registers };
+
+        // Make sure the controller is in idle state.
+        self.wait_until_ready_timeout(self.timeout_us())?;
+
+        // Issue flush requests for TX FIFO and RX FIFO.
+        controller
+            .SPI_FIFO_STATUS_0
+            .modify(SPI_FIFO_STATUS_0::RX_FIFO_FLUSH::SET + SPI_FIFO_STATUS_0::TX_FIFO_FLUSH::SET);
+
+        // Wait for both flushes to complete.
+        wait_until(
+            || {
+                !controller.SPI_FIFO_STATUS_0.is_set(SPI_FIFO_STATUS_0::RX_FIFO_FLUSH)
+                    && !controller.SPI_FIFO_STATUS_0.is_set(SPI_FIFO_STATUS_0::TX_FIFO_FLUSH)
+            },
+            self.timeout_us(),
+        )?;
+
+        Ok(())
+    }
+}
+
+/// A guard that restores the configuration of an SPI controller on drop.
+///
+/// Created through [`Spi::config_guard`].
+///
+/// [`Spi::config_guard`]: struct.Spi.html#method.config_guard
+#[derive(Debug)]
+pub struct ConfigGuard<'a> {
+    /// The controller whose configuration is restored.
+    spi: &'a Spi,
+    /// The configuration to restore.
+    state: SpiState,
+}
+
+impl Deref for ConfigGuard<'_> {
+    type Target = Spi;
+
+    fn deref(&self) -> &Spi {
+        self.spi
+    }
+}
+
+impl Drop for ConfigGuard<'_> {
+    fn drop(&mut self) {
+        // Transfers block until completion, so the controller is idle here.
+        let _ = self.spi.restore_state(&self.state);
+    }
+}
+
+/// A guard that keeps chip-select of an SPI controller asserted until it is dropped.
+///
+/// Created through [`Spi::select`].
+///
+/// [`Spi::select`]: struct.Spi.html#method.select
+#[derive(Debug)]
+pub struct CsGuard<'a> {
+    /// The controller whose chip-select is asserted.
+    spi: &'a Spi,
+    /// Whether chip-select was managed manually before the guard was created.
+    manual: bool,
+}
+
+impl Deref for CsGuard<'_> {
+    type Target = Spi;
+
+    fn deref(&self) -> &Spi {
+        self.spi
+    }
+}
+
+impl Drop for CsGuard<'_> {
+    fn drop(&mut self) {
+        // Deassert chip-select.
+        self.spi.drive_cs(false);
+
+        // Note the end of the frame.
+        self.spi.mark_activity();
+
+        self.spi.state().manual_cs.store(self.manual, Ordering::Relaxed);
+    }
+}
+
+/// A continuous, double-buffered DMA stream that is stopped when it is dropped.
+///
+/// Created through [`Spi::start_stream`]. The stream holds the borrows of both
+/// buffers, so the CPU can only access a buffer while the DMA engine doesn't.
+///
+/// ```no_run
+/// use libtegra::{apb::dma::Channel, spi::{Spi, StreamDirection}};
+///
+/// fn record(channel: &Channel, mut consume: impl FnMut(&[u8])) {
+///     let spi = Spi::SPI4;
+///     let mut buf_a = [0; 512];
+///     let mut buf_b = [0; 512];
+///
+///     // SAFETY: The stream is stopped before the buffers go out of scope.
+///     let mut stream = unsafe {
+///         spi.start_stream(channel, StreamDirection::Receive, &mut buf_a, &mut buf_b)
+///     }
+///     .unwrap();
+///
+///     for _ in 0..100 {
+///         while !stream.process_block(|block| consume(block)) {}
+///     }
+///
+///     stream.stop().unwrap();
+/// }
+/// ```
+///
+/// [`Spi::start_stream`]: struct.Spi.html#method.start_stream
+#[derive(Debug)]
+pub struct DmaStream<'a> {
+    /// The controller that runs the stream.
+    spi: &'a Spi,
+    /// The DMA channel that feeds or drains the controller.
+    channel: &'a Channel,
+    /// The direction of the stream.
+    direction: StreamDirection,
+    /// The buffers that are transferred in turns.
+    buffers: [&'a mut [u8]; 2],
+    /// The index of the buffer that is currently being transferred.
+    in_flight: usize,
+    /// Whether the stream was stopped explicitly.
+    stopped: bool,
+}
+
+impl DmaStream<'_> {
+    /// Hands the buffer that was just completed to the user, if any, and queues it again.
+    ///
+    /// If the DMA engine finished a block, the buffer is passed to `f` to
+    /// consume the received data or to refill it with the data to transmit.
+    /// The data cache is maintained around that. Afterwards, the buffer is
+    /// queued to be transferred after the one that is now in flight. Returns
+    /// whether a block was processed.
+    ///
+    /// NOTE: This must be called at least once per block, and `f` must return
+    /// before the block in flight completes, otherwise the stream transfers
+    /// a buffer that was not processed.
+    pub fn process_block<F: FnOnce(&mut [u8])>(&mut self, f: F) -> bool {
+        if !self.channel.take_block_done() {
+            return false;
+        }
+
+        // The DMA engine moved on to the other buffer.
+        let completed = self.in_flight;
+        self.in_flight ^= 1;
+
+        let buf = &mut *self.buffers[completed];
+        let (address, length) = (buf.as_ptr() as usize, buf.len());
+
+        if self.direction == StreamDirection::Receive {
+            invalidate_dcache_range(address, length);
+        }
+
+        f(&mut *buf);
+
+        if self.direction == StreamDirection::Transmit {
+            clean_dcache_range(address, length);
+        }
+
+        // Queue the buffer to follow the one in flight.
+        self.channel.set_next_address(buf.as_mut_ptr() as u32);
+
+        true
+    }
+
+    /// Stops the stream and checks the FIFO status for errors that occurred during it.
+    pub fn stop(mut self) -> Result<(), SpiError> {
+        self.stopped = true;
+
+        self.spi.stop_stream(self.channel)
+    }
+}
+
+impl Drop for DmaStream<'_> {
+    fn drop(&mut self) {
+        if !self.stopped {
+            let _ = self.spi.stop_stream(self.channel);
+        }
+    }
+}
+
+/// A wrapper around an SPI controller that serializes access from multiple contexts.
+///
+/// [`Spi`] instances can be freely copied, so nothing prevents the main loop
+/// and an interrupt handler from driving the same controller at once, which
+/// corrupts the transfer in flight. This wrapper runs every access to the
+/// controller within a critical section of the [`critical-section`] crate,
+/// so it can be shared safely, e.g. through a `static`.
+///
+/// ```no_run
+/// use libtegra::spi::{SharedSpi, Spi};
+///
+/// static SPI: SharedSpi = SharedSpi::new(Spi::SPI4);
+///
+/// let mut status = [0; 1];
+/// SPI.lock(|spi| spi.write_then_read(&[0x05], &mut status)).unwrap();
+/// ```
+///
+/// NOTE: Interrupts are masked for the whole duration of the closure, so
+/// transfers should be kept short. Single-context users can use [`Spi`]
+/// directly to avoid the overhead.
+///
+/// [`Spi`]: struct.Spi.html
+/// [`critical-section`]: https://docs.rs/critical-section/1.1
+#[cfg(feature = "critical-section")]
+#[derive(Debug)]
+pub struct SharedSpi {
+    /// The wrapped controller.
+    spi: Spi,
+}
+
+#[cfg(feature = "critical-section")]
+impl SharedSpi {
+    /// Wraps an SPI controller for shared access.
+    pub const fn new(spi: Spi) -> Self {
+        SharedSpi { spi }
+    }
+
+    /// Runs a closure with exclusive access to the controller.
+    pub fn lock<R>(&self, f: impl FnOnce(&Spi) -> R) -> R {
+        critical_section::with(|_| f(&self.spi))
+    }
+}
+
+#[cfg(feature = "critical-section")]
+unsafe impl Sync for SharedSpi {}
+
+#[cfg(test)]
+mod tests {
+    use super::{mock::MockSpi, *};
+
+    #[test]
+    fn controllers_are_mapped_0x200_bytes_apart() {
+        let controllers = [Spi::SPI1, Spi::SPI2, Spi::SPI3, Spi::SPI4];
+
+        for (n, spi) in controllers.iter().enumerate() {
+            assert_eq!(spi.registers as usize, 0x7000_D400 + n * 0x200);
+            assert_eq!(spi.index(), n);
+            assert_eq!(spi.dma_request(), 15 + n as u32);
+        }
+    }
+
+    #[test]
+    fn word_length_round_trips() {
+        let mock = MockSpi::new();
+        let spi = mock.spi();
+
+        for bits in 1..=32 {
+            spi.set_word_length(bits).unwrap();
+            assert_eq!(spi.word_length(), bits);
+            assert_eq!(
+                mock.registers().SPI_COMMAND_0.read(SPI_COMMAND_0::BIT_LEN),
+                u32::from(bits) - 1
+            );
+        }
+
+        assert_eq!(spi.set_word_length(0), Err(SpiError::InvalidArgument));
+        assert_eq!(spi.set_word_length(33), Err(SpiError::InvalidArgument));
+    }
+
+    #[test]
+    fn word_lengths_of_16_and_24_bits_are_set() {
+        let mock = MockSpi::new();
+        let spi = mock.spi();
+        let command = &mock.registers().SPI_COMMAND_0;
+
+        spi.set_word_length(16).unwrap();
+        assert_eq!(command.read(SPI_COMMAND_0::BIT_LEN), 15);
+        assert_eq!(spi.word_length(), 16);
+        assert_eq!(spi.bytes_per_packet(), 2);
+
+        spi.set_word_length(24).unwrap();
+        assert_eq!(command.read(SPI_COMMAND_0::BIT_LEN), 23);
+        assert_eq!(spi.word_length(), 24);
+        assert_eq!(spi.bytes_per_packet(), 3);
+    }
+
+    #[test]
+    fn invalid_word_lengths_are_rejected() {
+        let mock = MockSpi::new();
+        let spi = mock.spi();
+
+        spi.set_word_length(16).unwrap();
+
+        for &bits in [0, 33, 64, u8::max_value()].iter() {
+            assert_eq!(spi.set_word_length(bits), Err(SpiError::InvalidArgument));
+
+            // The previous word length stays in effect.
+            assert_eq!(spi.word_length(), 16);
+        }
+    }
+
+    #[test]
+    fn bytes_per_fifo_word_depends_on_packing() {
+        let mock = MockSpi::new();
+        let spi = mock.spi();
+
+        spi.set_word_length(8).unwrap();
+        assert_eq!(spi.bytes_per_fifo_word(), 1);
+
+        mock.registers().SPI_COMMAND_0.modify(SPI_COMMAND_0::PACKED::SET);
+        assert_eq!(spi.bytes_per_fifo_word(), 4);
+
+        spi.set_word_length(16).unwrap();
+        assert_eq!(spi.bytes_per_fifo_word(), 4);
+
+        spi.set_word_length(24).unwrap();
+        assert_eq!(spi.bytes_per_fifo_word(), 3);
+    }
+
+    #[test]
+    fn configuration_is_rejected_while_busy() {
+        let mock = MockSpi::new();
+        let spi = mock.spi();
+
+        mock.registers().SPI_TRANSFER_STATUS_0.modify(SPI_TRANSFER_STATUS_0::RDY::CLEAR);
+        mock.registers().SPI_COMMAND_0.modify(SPI_COMMAND_0::PIO::SET);
+        assert!(spi.is_busy());
+        assert_eq!(spi.set_mode(SpiMode::Mode3), Err(SpiError::Busy));
+
+        mock.registers().SPI_TRANSFER_STATUS_0.modify(SPI_TRANSFER_STATUS_0::RDY::SET);
+        assert!(!spi.is_busy());
+    }
+
+    #[test]
+    fn mode_sets_idle_data_level() {
+        let mock = MockSpi::new();
+        let spi = mock.spi();
+
+        let command = &mock.registers().SPI_COMMAND_0;
+
+        // Unrelated configuration must be preserved.
+        command.modify(SPI_COMMAND_0::CS_SEL::Cs2 + SPI_COMMAND_0::CS_SW_HW::SET);
+
+        // The mode, its CPOL and CPHA bits, and the idle level of the data line.
+        let modes = [
+            (SpiMode::Mode0, 0, 0, 0),
+            (SpiMode::Mode1, 0, 1, 0),
+            (SpiMode::Mode2, 1, 0, 1),
+            (SpiMode::Mode3, 1, 1, 1),
+        ];
+
+        for &(mode, cpol, cpha, idle_sda) in modes.iter() {
+            spi.set_mode(mode).unwrap();
+
+            // CPOL is bit 29 and CPHA is bit 28 of SPI_COMMAND_0.
+            assert_eq!((command.get() >> 29) & 1, cpol);
+            assert_eq!((command.get() >> 28) & 1, cpha);
+            assert_eq!(command.read(SPI_COMMAND_0::MODE), (cpol << 1) | cpha);
+            assert_eq!(command.read(SPI_COMMAND_0::IDLE_SDA), idle_sda);
+
+            assert_eq!(command.read(SPI_COMMAND_0::CS_SEL), 2);
+            assert!(command.is_set(SPI_COMMAND_0::CS_SW_HW));
+        }
+    }
+
+    #[test]
+    fn bit_order_round_trips() {
+        let mock = MockSpi::new();
+        let spi = mock.spi();
+
+        assert_eq!(spi.bit_order(), BitOrder::MsbFirst);
+
+        spi.set_bit_order(BitOrder::LsbFirst).unwrap();
+        assert_eq!(spi.bit_order(), BitOrder::LsbFirst);
+        assert!(mock.registers().SPI_COMMAND_0.is_set(SPI_COMMAND_0::EN_LE_BIT));
+    }
+
+    #[test]
+    fn saved_state_excludes_transfer_bits() {
+        let mock = MockSpi::new();
+        let spi = mock.spi();
+
+        spi.set_word_length(16).unwrap();
+        mock.registers().SPI_COMMAND_0.modify(SPI_COMMAND_0::TX_EN::SET);
+        let state = spi.save_state();
+
+        mock.registers().SPI_COMMAND_0.set(0);
+        spi.restore_state(&state).unwrap();
+
+        assert_eq!(spi.word_length(), 16);
+        assert!(!mock.registers().SPI_COMMAND_0.is_set(SPI_COMMAND_0::TX_EN));
+    }
+
+    #[test]
+    fn cs_state_survives_a_power_transition() {
+        let mock = MockSpi::new();
+        let spi = mock.spi();
+        let command = &mock.registers().SPI_COMMAND_0;
+
+        // Configure chip-select as a driver would before suspending.
+        spi.set_mode(SpiMode::Mode3).unwrap();
+        spi.set_chip_select(ChipSelect::Cs2).unwrap();
+        spi.set_cs_polarity(ChipSelect::Cs0, true).unwrap();
+        spi.set_cs_polarity(ChipSelect::Cs2, true).unwrap();
+        spi.set_cs_polarity(ChipSelect::Cs3, false).unwrap();
+        command.modify(SPI_COMMAND_0::CS_SW_HW::SET + SPI_COMMAND_0::CS_SW_VAL::SET);
+        let configured = command.get();
+        let state = spi.save_cs_state();
+
+        // The controller loses its configuration while powered down.
+        command.set(0);
+        spi.set_word_length(16).unwrap();
+
+        spi.restore_cs_state(&state).unwrap();
+
+        assert_eq!(command.read(SPI_COMMAND_0::MODE), 3);
+        assert_eq!(command.read(SPI_COMMAND_0::IDLE_SDA), 1);
+        assert_eq!(command.read(SPI_COMMAND_0::CS_SEL), 2);
+        assert!(command.is_set(SPI_COMMAND_0::CS_POL_INACTIVE_0));
+        assert!(!command.is_set(SPI_COMMAND_0::CS_POL_INACTIVE_1));
+        assert!(command.is_set(SPI_COMMAND_0::CS_POL_INACTIVE_2));
+        assert!(!command.is_set(SPI_COMMAND_0::CS_POL_INACTIVE_3));
+        assert!(command.is_set(SPI_COMMAND_0::CS_SW_HW));
+        assert!(command.is_set(SPI_COMMAND_0::CS_SW_VAL));
+
+        // All chip-select bits match and the rest of the configuration is kept.
+        let mask = Spi::cs_state_mask();
+        assert_eq!(command.get() & mask, configured & mask);
+        assert_eq!(spi.word_length(), 16);
+    }
+
+    #[test]
+    fn read_uninit_exposes_exactly_the_received_bytes() {
+        let mock = MockSpi::new();
+        let spi = mock.spi();
+        let mut buf = [MaybeUninit::uninit(); 32];
+
+        mock.set_response(Some(0x1234_5678));
+
+        // In unpacked mode, every byte is taken from its own FIFO word.
+        let received = spi.read_uninit(&mut buf[..3]).unwrap();
+        assert_eq!(received, &[0x78; 3]);
+
+        // In packed mode, four bytes are taken from every FIFO word.
+        let received = spi.read_uninit(&mut buf[..20]).unwrap();
+        assert_eq!(received.len(), 20);
+        for word in received.chunks(4) {
+            assert_eq!(word, &[0x78, 0x56, 0x34, 0x12]);
+        }
+
+        // Nothing is exposed if the transfer can't be carried out.
+        spi.set_word_length(16).unwrap();
+        assert_eq!(spi.read_uninit(&mut buf[..3]), Err(SpiError::InvalidLength));
+    }
+
+    #[test]
+    fn min_cs_inactive_time_is_converted_into_cycles() {
+        let mock = MockSpi::new();
+        let spi = mock.spi();
+        let timing = &mock.registers().SPI_TIMING_REG2_0;
+        let software_us = || spi.state().min_cs_inactive_us.load(Ordering::Relaxed);
+
+        // A microsecond at 25.5 MHz is rounded up to 26 cycles.
+        mock.set_clock_rate(DEFAULT_FREQUENCY);
+        spi.set_min_cs_inactive_us(1).unwrap();
+        assert_eq!(timing.read(SPI_TIMING_REG2_0::CYCLES_BETWEEN_PACKETS_0), 26);
+        assert_eq!(software_us(), 0);
+
+        // Up to 31 cycles are inserted by the hardware.
+        mock.set_clock_rate(1_000_000);
+        spi.set_min_cs_inactive_us(31).unwrap();
+        assert_eq!(timing.read(SPI_TIMING_REG2_0::CYCLES_BETWEEN_PACKETS_0), 31);
+        assert_eq!(software_us(), 0);
+
+        // The cycles are programmed for the selected chip-select line.
+        spi.set_chip_select(ChipSelect::Cs2).unwrap();
+        spi.set_min_cs_inactive_us(7).unwrap();
+        assert_eq!(timing.read(SPI_TIMING_REG2_0::CYCLES_BETWEEN_PACKETS_2), 7);
+        assert_eq!(timing.read(SPI_TIMING_REG2_0::CYCLES_BETWEEN_PACKETS_0), 31);
+    }
+
+    #[test]
+    fn min_cs_inactive_time_falls_back_to_software() {
+        let mock = MockSpi::new();
+        let spi = mock.spi();
+        let timing = &mock.registers().SPI_TIMING_REG2_0;
+        let software_us = || spi.state().min_cs_inactive_us.load(Ordering::Relaxed);
+
+        // More than 31 cycles don't fit into the field.
+        mock.set_clock_rate(1_000_000);
+        spi.set_min_cs_inactive_us(32).unwrap();
+        assert_eq!(timing.read(SPI_TIMING_REG2_0::CYCLES_BETWEEN_PACKETS_0), 0);
+        assert_eq!(software_us(), 32);
+
+        // Large times must not overflow the conversion.
+        mock.set_clock_rate(MAX_FREQUENCY);
+        spi.set_min_cs_inactive_us(u32::max_value()).unwrap();
+        assert_eq!(timing.read(SPI_TIMING_REG2_0::CYCLES_BETWEEN_PACKETS_0), 0);
+        assert_eq!(software_us(), u32::max_value());
+
+        // Without a known clock rate, no cycles can be computed.
+        mock.set_clock_rate(0);
+        spi.set_min_cs_inactive_us(1).unwrap();
+        assert_eq!(timing.read(SPI_TIMING_REG2_0::CYCLES_BETWEEN_PACKETS_0), 0);
+        assert_eq!(software_us(), 1);
+    }
+
+    #[test]
+    fn configuration_is_applied_before_pio_is_set() {
+        let mock = MockSpi::new();
+        let spi = mock.spi();
+        let pio = SPI_COMMAND_0::PIO::Go.value;
+
+        spi.set_word_length(16).unwrap();
+        spi.pio_send_packet(&[0x34, 0x12]).unwrap();
+
+        // Find the first state in which the controller saw the PIO bit.
+        let trace = mock.command_trace();
+        let started = trace.iter().position(|command| command & pio != 0).unwrap();
+        let configured = trace[started - 1];
+
+        // Before that, the controller already saw the complete configuration,
+        // without the PIO bit, and only the PIO bit was added afterwards.
+        assert_eq!(configured & pio, 0);
+        assert_eq!(trace[started], configured | pio);
+
+        let bit_len = SPI_COMMAND_0::BIT_LEN.val(0x1F).value;
+        assert_ne!(configured & SPI_COMMAND_0::TX_EN::SET.value, 0);
+        assert_eq!(configured & SPI_COMMAND_0::RX_EN::SET.value, 0);
+        assert_eq!(configured & SPI_COMMAND_0::PACKED::SET.value, 0);
+        assert_eq!(configured & bit_len, SPI_COMMAND_0::BIT_LEN.val(15).value);
+    }
+
+    /// Checks the blocks of a transfer against the FIFO constraints.
+    fn check_pio_blocks(length: usize, packable: bool, bytes_per_packet: usize) {
+        let packed_length = pio_packed_length(length, packable, PACKED_THRESHOLD);
+
+        let mut expected_offset = 0;
+        for (offset, block, packed) in pio_blocks(length, packed_length, bytes_per_packet) {
+            // Blocks are contiguous and never empty.
+            assert_eq!(offset, expected_offset);
+            assert_ne!(block, 0);
+
+            if packed {
+                // Packed blocks consist of whole FIFO words within the packed part.
+                assert!(block <= FIFO_DEPTH * 4);
+                assert_eq!(block % 4, 0);
+                assert!(offset + block <= packed_length);
+            } else {
+                // Unpacked blocks consist of whole words after the packed part.
+                assert!(block <= FIFO_DEPTH * bytes_per_packet);
+                assert_eq!(block % bytes_per_packet, 0);
+                assert!(offset >= packed_length);
+            }
+
+            expected_offset += block;
+        }
+
+        // The blocks cover the whole transfer.
+        assert_eq!(expected_offset, length);
+    }
+
+    #[test]
+    fn pio_blocks_cover_arbitrary_lengths() {
+        for &bytes_per_packet in &[1, 2, 4] {
+            for length in (0..=4 * FIFO_DEPTH * 4 + 7).filter(|l| l % bytes_per_packet == 0) {
+                check_pio_blocks(length, true, bytes_per_packet);
+                check_pio_blocks(length, false, bytes_per_packet);
+            }
+        }
+
+        // Word lengths that can't be packed, e.g. 24 bits.
+        for length in (0..=2 * FIFO_DEPTH * 3 + 6).step_by(3) {
+            check_pio_blocks(length, false, 3);
+        }
+    }
+
+    #[test]
+    fn pio_blocks_pack_only_above_threshold() {
+        let blocks = |length| {
+            let packed_length = pio_packed_length(length, true, PACKED_THRESHOLD);
+
+            pio_blocks(length, packed_length, 1).collect::<std::vec::Vec<_>>()
+        };
+
+        // Short transfers stay unpacked.
+        assert_eq!(blocks(PACKED_THRESHOLD - 1), [(0, PACKED_THRESHOLD - 1, false)]);
+
+        // A trailing partial FIFO word falls back to unpacked mode.
+        assert_eq!(
+            blocks(PACKED_THRESHOLD + 3),
+            [(0, PACKED_THRESHOLD, true), (PACKED_THRESHOLD, 3, false)]
+        );
+
+        // Long transfers are split at the FIFO depth.
+        assert_eq!(
+            blocks(FIFO_DEPTH * 4 + 4),
+            [(0, FIFO_DEPTH * 4, true), (FIFO_DEPTH * 4, 4, true)]
+        );
+
+        // Word lengths that can't be packed never are.
+        assert_eq!(pio_packed_length(FIFO_DEPTH * 4, false, PACKED_THRESHOLD), 0);
+    }
+
+    #[test]
+    fn clock_divisor_does_not_exceed_requested_rate() {
+        // The default rate is reached exactly.
+        assert_eq!(
+            clock_divisor(PLLP_OUT0_RATE, DEFAULT_FREQUENCY),
+            Some((30, DEFAULT_FREQUENCY))
+        );
+
+        // Rates in between steps are rounded down.
+        assert_eq!(clock_divisor(PLLP_OUT0_RATE, 50_000_000), Some((15, 48_000_000)));
+
+        for hz in (100_000..=MAX_FREQUENCY).step_by(9_973) {
+            for &parent in &[PLLP_OUT0_RATE, 38_400_000, 12_000_000] {
+                if let Some((divisor, rate)) = clock_divisor(parent, hz) {
+                    assert!(divisor <= 0xFF);
+                    assert!(rate <= hz);
+
+                    // The next faster step would exceed the requested rate.
+                    if divisor > 0 {
+                        assert!(u64::from(parent) * 2 > u64::from(hz) * u64::from(divisor + 1));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn clock_divisor_rejects_unreachable_rates() {
+        assert_eq!(clock_divisor(PLLP_OUT0_RATE, 0), None);
+        assert_eq!(clock_divisor(PLLP_OUT0_RATE, 1_000_000), None);
+
+        // Low rates are reached from CLK_M instead.
+        assert_eq!(clock_divisor(38_400_000, 1_000_000), Some((75, 997_402)));
+        assert!(clock_divisor(38_400_000, MIN_FREQUENCY).is_some());
+        assert_eq!(clock_divisor(38_400_000, MIN_FREQUENCY - 1), None);
+    }
+
+    #[test]
+    fn clock_cycles_round_up_to_microseconds() {
+        assert_eq!(clock_cycles_to_us(DEFAULT_FREQUENCY, 0), 0);
+        assert_eq!(clock_cycles_to_us(DEFAULT_FREQUENCY, 1), 1);
+        assert_eq!(clock_cycles_to_us(DEFAULT_FREQUENCY, 25), 1);
+        assert_eq!(clock_cycles_to_us(DEFAULT_FREQUENCY, 26), 2);
+        assert_eq!(clock_cycles_to_us(1_000_000, 4), 4);
+
+        // The slowest rate yields the longest delay.
+        assert_eq!(clock_cycles_to_us(MIN_FREQUENCY, 4), 14);
+
+        // Large amounts of cycles don't overflow the intermediate product.
+        assert_eq!(clock_cycles_to_us(DEFAULT_FREQUENCY, u32::max_value()), 168_430_090);
+    }
+
+    #[test]
+    fn cycles_to_us_assumes_slowest_rate_if_unknown() {
+        let mock = MockSpi::new();
+
+        assert_eq!(mock.spi().cycles_to_us(4), clock_cycles_to_us(MIN_FREQUENCY, 4));
+    }
+
+    #[test]
+    fn words_are_packed_little_endian() {
+        assert_eq!(pack_word(&[]), 0);
+        assert_eq!(pack_word(&[0x12]), 0x0000_0012);
+        assert_eq!(pack_word(&[0x12, 0x34]), 0x0000_3412);
+        assert_eq!(pack_word(&[0x12, 0x34, 0x56]), 0x0056_3412);
+        assert_eq!(pack_word(&[0x12, 0x34, 0x56, 0x78]), 0x7856_3412);
+    }
+
+    #[test]
+    fn spi_words_round_trip_little_endian() {
+        let mut bytes = [0; 4];
+
+        0x1234u16.write_le(&mut bytes[..2]);
+        assert_eq!(bytes[..2], [0x34, 0x12]);
+        assert_eq!(u16::read_le(&bytes[..2]), 0x1234);
+
+        0x1234_5678u32.write_le(&mut bytes);
+        assert_eq!(bytes, [0x78, 0x56, 0x34, 0x12]);
+        assert_eq!(u32::read_le(&bytes), 0x1234_5678);
+        assert_eq!(pack_word(&bytes), 0x1234_5678);
+    }
+
+    #[test]
+    fn chip_selects_are_converted_from_numbers() {
+        assert_eq!(ChipSelect::try_from(0), Ok(ChipSelect::Cs0));
+        assert_eq!(ChipSelect::try_from(1), Ok(ChipSelect::Cs1));
+        assert_eq!(ChipSelect::try_from(2), Ok(ChipSelect::Cs2));
+        assert_eq!(ChipSelect::try_from(3), Ok(ChipSelect::Cs3));
+        assert_eq!(ChipSelect::try_from(4), Err(SpiError::InvalidArgument));
+    }
+
+    #[test]
+    fn chip_selects_land_in_cs_sel() {
+        let mock = MockSpi::new();
+        let spi = mock.spi();
+        let command = &mock.registers().SPI_COMMAND_0;
+
+        // Preset all other bits, which must be preserved.
+        command.set(!(0b11 << 26) & !SPI_COMMAND_0::PIO::SET.value);
+
+        let selects = [
+            (ChipSelect::Cs3, 3),
+            (ChipSelect::Cs0, 0),
+            (ChipSelect::Cs2, 2),
+            (ChipSelect::Cs1, 1),
+        ];
+
+        for &(cs, value) in selects.iter() {
+            spi.set_chip_select(cs).unwrap();
+
+            // CS_SEL occupies bits 27 and 26 of SPI_COMMAND_0.
+            assert_eq!(command.read(SPI_COMMAND_0::CS_SEL), value);
+            assert_eq!((command.get() >> 26) & 0b11, value);
+            assert_eq!(command.get() | (0b11 << 26), !SPI_COMMAND_0::PIO::SET.value);
+        }
+    }
+
+    #[test]
+    fn fifo_counts_are_derived_from_status() {
+        let mock = MockSpi::new();
+        let spi = mock.spi();
+
+        mock.registers().SPI_FIFO_STATUS_0.write(
+            SPI_FIFO_STATUS_0::TX_FIFO_EMPTY_COUNT.val(60)
+                + SPI_FIFO_STATUS_0::RX_FIFO_FULL_COUNT.val(3),
+        );
+
+        assert_eq!(spi.tx_fifo_free(), 60);
+        assert_eq!(spi.tx_fifo_count(), 4);
+        assert_eq!(spi.rx_fifo_count(), 3);
+    }
+
+    #[test]
+    fn mocks_keep_separate_state() {
+        let first = MockSpi::new();
+        let second = MockSpi::new();
+
+        first.spi().set_timeout(10);
+        second.spi().set_timeout(20);
+
+        assert_eq!(first.spi().timeout_us(), 10);
+        assert_eq!(second.spi().timeout_us(), 20);
+        assert_eq!(Spi::SPI1.timeout_us(), DEFAULT_TIMEOUT_US);
+    }
+
+    #[test]
+    fn fifos_are_flushed() {
+        let mock = MockSpi::new();
+        let status = &mock.registers().SPI_FIFO_STATUS_0;
+
+        mock.spi().flush_fifos().unwrap();
+        assert!(!status.is_set(SPI_FIFO_STATUS_0::RX_FIFO_FLUSH));
+        assert!(!status.is_set(SPI_FIFO_STATUS_0::TX_FIFO_FLUSH));
+    }
+
+    #[test]
+    fn pio_send_packet_completes() {
+        let mock = MockSpi::new();
+        let spi = mock.spi();
+        let registers = mock.registers();
+
+        spi.pio_send_packet(&[0x12, 0x34, 0x56]).unwrap();
+
+        // The words were loaded one after another, the last one remains.
+        assert_eq!(registers.SPI_TX_FIFO_0.get(), 0x56);
+        assert_eq!(registers.SPI_DMA_BLK_SIZE_0.get(), 2);
+        assert!(registers.SPI_TRANSFER_STATUS_0.is_set(SPI_TRANSFER_STATUS_0::RDY));
+        assert!(!registers.SPI_COMMAND_0.is_set(SPI_COMMAND_0::PIO));
+        assert!(!registers.SPI_COMMAND_0.is_set(SPI_COMMAND_0::TX_EN));
+    }
+
+    #[test]
+    fn stuck_transfer_times_out() {
+        let mock = MockSpi::new();
+        let spi = mock.spi();
+
+        // Keep the controller from ever becoming ready.
+        mock.registers().SPI_TRANSFER_STATUS_0.modify(SPI_TRANSFER_STATUS_0::RDY::CLEAR);
+        spi.set_timeout(100);
+
+        assert_eq!(spi.pio_send_packet(&[0xAB]), Err(SpiError::Timeout));
+    }
+
+    #[test]
+    fn exchange_returns_the_response() {
+        let mock = MockSpi::new();
+        let spi = mock.spi();
+
+        // Read the JEDEC ID of an SPI flash, which answers with 0xEF.
+        mock.set_response(Some(0xEF));
+        assert_eq!(spi.exchange(&[0x9F]), Ok(&[0xEF][..]));
+        assert_eq!(mock.registers().SPI_TX_FIFO_0.get(), 0x9F);
+
+        // Without a response word, the command is looped back.
+        mock.set_response(None);
+        assert_eq!(spi.exchange(&[0x05]), Ok(&[0x05][..]));
+
+        assert_eq!(spi.exchange(&[]), Ok(&[][..]));
+        assert_eq!(
+            spi.exchange(&[0; EXCHANGE_BUFFER_SIZE + 1]),
+            Err(SpiError::BufferTooLarge)
+        );
+    }
+
+    #[test]
+    fn exchange_is_not_reentrant() {
+        let mock = MockSpi::new();
+        let spi = mock.spi();
+
+        // Pretend that an exchange was preempted.
+        spi.state().exchanging.store(true, Ordering::Relaxed);
+        assert_eq!(spi.exchange(&[0x9F]), Err(SpiError::Busy));
+
+        spi.state().exchanging.store(false, Ordering::Relaxed);
+        assert!(spi.exchange(&[0x9F]).is_ok());
     }
 }
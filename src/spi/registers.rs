@@ -308,6 +308,12 @@ register_bitfields! {
         /// Whether Continous Mode transfer should be enabled.
         CONT OFFSET(30) NUMBITS(1) [],
 
+        /// Whether an interrupt should be raised when the RX FIFO trigger level is reached.
+        IE_RX OFFSET(29) NUMBITS(1) [],
+
+        /// Whether an interrupt should be raised when the TX FIFO trigger level is reached.
+        IE_TX OFFSET(28) NUMBITS(1) [],
+
         /// Reserved for future use.
         ///
         /// NOTE: Always write `0`.
@@ -316,13 +322,13 @@ register_bitfields! {
         /// Receive FIFO trigger level.
         RX_TRIG OFFSET(19) NUMBITS(2) [
             /// DMA trigger is asserted when there is at least 1 packet in RX FIFO.
-            OneWord = 00,
+            OneWord = 0,
             /// DMA trigger is asserted when there are at least 4 packets in RX FIFO.
-            FourWords = 01,
+            FourWords = 1,
             /// DMA trigger is asserted when there are at least 8 packets in RX FIFO.
-            EightWords = 10,
+            EightWords = 2,
             /// DMA trigger is asserted when there are at least 16 packets in RX FIFO.
-            SixteenWords = 11
+            SixteenWords = 3
         ],
 
         /// Reserved for future use.
@@ -333,13 +339,13 @@ register_bitfields! {
         /// Transmit FIFO trigger level.
         TX_TRIG OFFSET(15) NUMBITS(2) [
             /// DMA trigger is asserted whenever there is space for at least 1 packet in TX FIFO.
-            OneWord = 00,
+            OneWord = 0,
             /// DMA trigger is asserted whenever there is space for at least 4 packets in TX FIFO.
-            FourWords = 01,
+            FourWords = 1,
             /// DMA trigger is asserted whenever there is space for at least 8 packets in TX FIFO.
-            EightWords = 10,
+            EightWords = 2,
             /// DMA trigger is asserted whenever there is space for at least 16 packets in TX FIFO.
-            SixteenWords = 11
+            SixteenWords = 3
         ]
     ],
 
@@ -0,0 +1,21 @@
+//! Implementations of the [`embedded-hal`] delay traits.
+//!
+//! [`embedded-hal`]: https://docs.rs/embedded-hal/1.0.0
+
+use embedded_hal::delay::DelayNs;
+
+use super::{msleep, nsleep, usleep, Delay};
+
+impl DelayNs for Delay {
+    fn delay_ns(&mut self, ns: u32) {
+        nsleep(ns);
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        usleep(us);
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        msleep(ms);
+    }
+}
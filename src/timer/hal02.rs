@@ -0,0 +1,21 @@
+//! Implementations of the [`embedded-hal`] 0.2 timer traits.
+//!
+//! [`embedded-hal`]: https://docs.rs/embedded-hal/0.2
+
+use embedded_hal_02::timer;
+use void::Void;
+
+use super::CountDown;
+
+impl timer::CountDown for CountDown {
+    /// The duration of the countdown in microseconds.
+    type Time = u32;
+
+    fn start<T: Into<u32>>(&mut self, count: T) {
+        CountDown::start(self, count.into());
+    }
+
+    fn wait(&mut self) -> nb::Result<(), Void> {
+        CountDown::wait(self)
+    }
+}
@@ -0,0 +1,69 @@
+//! A software microsecond counter for running timer-based code on the host.
+//!
+//! When this backend is enabled, [`get_microseconds`] and everything built on
+//! top of it, such as [`usleep`], [`wait_until`] and [`Instant`], are driven
+//! by a counter in host memory instead of `TIMERUS_CNTR_1US_0`. Every read
+//! advances the counter by a configurable step, so polling loops make progress
+//! and eventually time out, just like on the hardware.
+//!
+//! The counter is kept per thread, so tests that run in parallel don't
+//! interfere with each other.
+//!
+//! NOTE: Emulated peripherals can register a hook through [`set_tick_hook`],
+//! which is called on every read of the counter. This lets them react to the
+//! driver while it polls, like the hardware would in the meantime.
+//!
+//! [`get_microseconds`]: ../fn.get_microseconds.html
+//! [`usleep`]: ../fn.usleep.html
+//! [`wait_until`]: ../fn.wait_until.html
+//! [`Instant`]: ../struct.Instant.html
+//! [`set_tick_hook`]: fn.set_tick_hook.html
+
+use std::cell::Cell;
+
+thread_local! {
+    /// The current value of the counter, in microseconds.
+    static MICROSECONDS: Cell<u32> = Cell::new(0);
+
+    /// The amount of microseconds that the counter advances by on every read.
+    static STEP: Cell<u32> = Cell::new(1);
+
+    /// The function that is called on every read of the counter.
+    static TICK_HOOK: Cell<Option<fn()>> = Cell::new(None);
+}
+
+/// Sets the value of the counter, in microseconds.
+pub fn set_microseconds(value: u32) {
+    MICROSECONDS.with(|counter| counter.set(value));
+}
+
+/// Sets the amount of microseconds that the counter advances by on every read.
+///
+/// By default, the counter advances by a single microsecond per read. A step
+/// of `0` freezes the counter, which makes waits without a deadline hang.
+pub fn set_step(step: u32) {
+    STEP.with(|cell| cell.set(step));
+}
+
+/// Sets the function to be called on every read of the counter, or removes it.
+pub fn set_tick_hook(hook: Option<fn()>) {
+    TICK_HOOK.with(|cell| cell.set(hook));
+}
+
+/// Reads the counter and advances it by the configured step.
+///
+/// The counter wraps around at `u32::MAX`, just like `TIMERUS_CNTR_1US_0`.
+pub(crate) fn read_microseconds() -> u32 {
+    let value = MICROSECONDS.with(|counter| {
+        let value = counter.get();
+        counter.set(value.wrapping_add(STEP.with(Cell::get)));
+
+        value
+    });
+
+    if let Some(hook) = TICK_HOOK.with(Cell::get) {
+        hook();
+    }
+
+    value
+}
@@ -33,28 +33,884 @@
 //!
 //! usleep(5_000_000); // Delays execution for five seconds.
 //! ```
+//!
+//! ## WDT
+//!
+//! The watchdogs reset the system unless they are petted in time.
+//!
+//! ```no_run
+//! use libtegra::timer::Watchdog;
+//!
+//! Watchdog::WDT4.start(5_000_000).unwrap(); // Resets the system within five seconds.
+//!
+//! loop {
+//!     // Do some work.
+//!
+//!     Watchdog::WDT4.pet();
+//! }
+//! ```
+
+use core::{
+    fmt, iter, mem,
+    ops::BitOr,
+    sync::atomic::{AtomicU32, AtomicUsize, Ordering},
+};
 
+#[cfg(target_arch = "aarch64")]
+use cortex_a::regs::{RegisterReadWrite, DAIF};
+use cortex_a::regs::{RegisterReadOnly, CNTFRQ_EL0, CNTPCT_EL0};
+
+use register::{mmio::ReadWrite, Field, RegisterLongName};
+
+use void::Void;
+
+use crate::{car, gpio::Gpio, pmc};
+
+#[cfg(feature = "embedded-hal")]
+mod hal;
+#[cfg(feature = "embedded-hal-02")]
+mod hal02;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
 pub mod rtc;
 pub mod timerus;
+pub mod tmr;
+pub mod wdt;
+
+/// The span over which the microsecond counter is sampled by [`self_test`].
+///
+/// [`self_test`]: fn.self_test.html
+const SELF_TEST_SPAN_US: u32 = 1000;
+
+/// The tolerated deviation of the microsecond counter from its expected rate in [`self_test`], in percent.
+///
+/// [`self_test`]: fn.self_test.html
+const SELF_TEST_TOLERANCE: u32 = 10;
+
+/// The largest trigger value of a TMR timer.
+const MAX_TIMER_TRIGGER: u32 = 0x1FFF_FFFF;
+
+/// The number of expirations of its source timer after which a [`Watchdog`] resets the system.
+///
+/// [`Watchdog`]: struct.Watchdog.html
+const WATCHDOG_EXPIRATIONS: u32 = 4;
+
+/// The longest span in microseconds that is waited for in one go by the sleep functions.
+const MAX_SLEEP_CHUNK_US: u32 = u32::max_value() / 2;
+
+/// The value of `TIMERUS_CNTR_1US_0` observed by the last call to [`monotonic_us`].
+///
+/// [`monotonic_us`]: fn.monotonic_us.html
+static MONOTONIC_LOW: AtomicU32 = AtomicU32::new(0);
+
+/// The software-maintained upper word of [`monotonic_us`].
+///
+/// [`monotonic_us`]: fn.monotonic_us.html
+static MONOTONIC_HIGH: AtomicU32 = AtomicU32::new(0);
+
+/// The callbacks of the TMR timers that are invoked by [`Timer::handle_interrupt`].
+///
+/// Every entry holds a function pointer, or 0 if no callback is registered.
+///
+/// [`Timer::handle_interrupt`]: struct.Timer.html#method.handle_interrupt
+static TIMER_CALLBACKS: [AtomicUsize; 10] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+
+/// Enumeration of potential errors that may be detected by [`self_test`].
+///
+/// [`self_test`]: fn.self_test.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TimerError {
+    /// The microsecond counter doesn't advance at all.
+    Stuck,
+    /// The microsecond counter went backwards.
+    NotMonotonic,
+    /// The microsecond counter advances at a wrong rate.
+    WrongRate,
+}
+
+/// The error that is returned by [`wait_until`] if its condition wasn't met in time.
+///
+/// [`wait_until`]: fn.wait_until.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TimeoutError;
+
+/// The error that is returned when arming a [`Timer`] with a duration it cannot count.
+///
+/// [`Timer`]: struct.Timer.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InvalidDurationError;
+
+impl fmt::Display for TimerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TimerError::Stuck => "microsecond counter stuck",
+            TimerError::NotMonotonic => "microsecond counter not monotonic",
+            TimerError::WrongRate => "microsecond counter runs at a wrong rate",
+        })
+    }
+}
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("operation timed out")
+    }
+}
+
+impl fmt::Display for InvalidDurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("timer duration out of range")
+    }
+}
+
+/// Enumeration of the supported clk_m frequencies.
+///
+/// The clk_m oscillator drives the fixed time base that
+/// the microsecond counter is derived from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClkMFreq {
+    /// A clk_m frequency of 12 MHz.
+    Mhz12,
+    /// A clk_m frequency of 38.4 MHz.
+    Mhz384,
+}
+
+/// Enumeration of the cores whose debug state can freeze the timers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FreezeCore {
+    /// The CPU core 0.
+    Cpu0,
+    /// The CPU core 1.
+    Cpu1,
+    /// The CPU core 2.
+    Cpu2,
+    /// The CPU core 3.
+    Cpu3,
+    /// The COP, i.e. the BPMP.
+    Cop,
+}
+
+/// Sets whether the timers should be frozen while a core is in debug state.
+///
+/// Freezing the timers while a core is halted, e.g. under JTAG, keeps delay
+/// loops from overshooting their deadline as soon as the core resumes. By
+/// default, the timers are not frozen.
+pub fn set_debug_freeze(core: FreezeCore, freeze: bool) {
+    let timerus = unsafe { &*timerus::REGISTERS };
+    let value = freeze as u32;
+
+    let field = match core {
+        FreezeCore::Cpu0 => timerus::TIMERUS_CNTR_FREEZE_0::DBG_FREEZE_CPU0.val(value),
+        FreezeCore::Cpu1 => timerus::TIMERUS_CNTR_FREEZE_0::DBG_FREEZE_CPU1.val(value),
+        FreezeCore::Cpu2 => timerus::TIMERUS_CNTR_FREEZE_0::DBG_FREEZE_CPU2.val(value),
+        FreezeCore::Cpu3 => timerus::TIMERUS_CNTR_FREEZE_0::DBG_FREEZE_CPU3.val(value),
+        FreezeCore::Cop => timerus::TIMERUS_CNTR_FREEZE_0::DBG_FREEZE_COP.val(value),
+    };
+    timerus.TIMERUS_CNTR_FREEZE_0.modify(field);
+}
+
+/// Initializes the fixed time base for the clk_m frequency of the board.
+///
+/// `TIMERUS_USEC_CFG_0` is programmed with the divider that turns clk_m
+/// into microseconds. If it doesn't match the actual clk_m frequency, the
+/// microsecond counter runs at a wrong rate, e.g. more than 3 times too
+/// fast when clk_m runs at 38.4 MHz with the 12 MHz divider.
+///
+/// NOTE: This function must be called once at boot, before any of the
+/// delay functions, such as [`usleep`], are reliable.
+///
+/// [`usleep`]: fn.usleep.html
+pub fn init(freq: ClkMFreq) {
+    reconfigure(freq);
+}
+
+/// Detects the clk_m frequency from the `OSC_FREQ` field of the CAR oscillator control.
+///
+/// Returns `None` if the oscillator runs at a frequency that is not
+/// supported by [`ClkMFreq`]. The result can be passed to [`init`],
+/// so callers don't need to know the crystal of the board.
+///
+/// [`ClkMFreq`]: enum.ClkMFreq.html
+/// [`init`]: fn.init.html
+pub fn detect_clk_m_freq() -> Option<ClkMFreq> {
+    use car::CLK_RST_CONTROLLER_OSC_CTRL_0::OSC_FREQ;
+
+    let osc_ctrl = unsafe { &*car::OSC_CTRL };
+
+    match osc_ctrl.read(OSC_FREQ) {
+        freq if freq == OSC_FREQ::Value::Osc38p4 as u32 => Some(ClkMFreq::Mhz384),
+        freq if freq == OSC_FREQ::Value::Osc12 as u32 => Some(ClkMFreq::Mhz12),
+        _ => None,
+    }
+}
+
+/// A set of cores whose debug state freezes the timers.
+///
+/// Sets are combined through the `|` operator, e.g.
+/// `DebugFreezeMask::CPU0 | DebugFreezeMask::COP`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DebugFreezeMask(u32);
+
+impl DebugFreezeMask {
+    /// No core freezes the timers.
+    pub const NONE: Self = DebugFreezeMask(0);
+    /// The CPU core 0.
+    pub const CPU0: Self = DebugFreezeMask(1 << 0);
+    /// The CPU core 1.
+    pub const CPU1: Self = DebugFreezeMask(1 << 1);
+    /// The CPU core 2.
+    pub const CPU2: Self = DebugFreezeMask(1 << 2);
+    /// The CPU core 3.
+    pub const CPU3: Self = DebugFreezeMask(1 << 3);
+    /// The COP, i.e. the BPMP.
+    pub const COP: Self = DebugFreezeMask(1 << 4);
+    /// All of the cores.
+    pub const ALL: Self = DebugFreezeMask(0x1F);
+
+    /// Whether all the cores of another set are contained in this one.
+    pub fn contains(&self, other: DebugFreezeMask) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for DebugFreezeMask {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        DebugFreezeMask(self.0 | rhs.0)
+    }
+}
+
+/// Sets the cores whose debug state freezes the timers, all at once.
+///
+/// Unlike [`set_debug_freeze`], this configures all cores in a single
+/// write. Cores that are not contained in the set don't freeze the timers.
+/// By default, none of them do.
+///
+/// [`set_debug_freeze`]: fn.set_debug_freeze.html
+pub fn set_debug_freeze_mask(mask: DebugFreezeMask) {
+    let timerus = unsafe { &*timerus::REGISTERS };
+
+    timerus.TIMERUS_CNTR_FREEZE_0.modify(
+        timerus::TIMERUS_CNTR_FREEZE_0::DBG_FREEZE_CPU0.val(mask.contains(DebugFreezeMask::CPU0) as u32)
+        + timerus::TIMERUS_CNTR_FREEZE_0::DBG_FREEZE_CPU1.val(mask.contains(DebugFreezeMask::CPU1) as u32)
+        + timerus::TIMERUS_CNTR_FREEZE_0::DBG_FREEZE_CPU2.val(mask.contains(DebugFreezeMask::CPU2) as u32)
+        + timerus::TIMERUS_CNTR_FREEZE_0::DBG_FREEZE_CPU3.val(mask.contains(DebugFreezeMask::CPU3) as u32)
+        + timerus::TIMERUS_CNTR_FREEZE_0::DBG_FREEZE_COP.val(mask.contains(DebugFreezeMask::COP) as u32)
+    );
+}
+
+/// Reprograms the fixed time base for a new clk_m frequency.
+///
+/// When firmware switches clk_m to a different frequency, e.g. as part
+/// of a power state change, the `TIMERUS_USEC_CFG_0` divider no longer
+/// yields microseconds and must be updated through this function.
+///
+/// The microsecond counter itself keeps its value across the switch,
+/// so [`get_microseconds`] stays continuous. However, the counter runs
+/// at a wrong rate in the brief window between the clock switch and the
+/// call to this function, so delays such as [`usleep`] should not be in
+/// flight during reconfiguration.
+///
+/// [`get_microseconds`]: fn.get_microseconds.html
+/// [`usleep`]: fn.usleep.html
+pub fn reconfigure(new_freq: ClkMFreq) {
+    let timerus = unsafe { &*timerus::REGISTERS };
+
+    match new_freq {
+        ClkMFreq::Mhz12 => timerus.TIMERUS_USEC_CFG_0.write(
+            timerus::TIMERUS_USEC_CFG_0::USEC_DIVIDEND::ClkMFreq12
+            + timerus::TIMERUS_USEC_CFG_0::USEC_DIVISOR::ClkMFreq12
+        ),
+        ClkMFreq::Mhz384 => timerus.TIMERUS_USEC_CFG_0.write(
+            timerus::TIMERUS_USEC_CFG_0::USEC_DIVIDEND::ClkMFreq384
+            + timerus::TIMERUS_USEC_CFG_0::USEC_DIVISOR::ClkMFreq384
+        ),
+    }
+}
+
+/// Validates that the microsecond counter is alive and ticks at the expected rate.
+///
+/// `TIMERUS_CNTR_1US_0` is sampled repeatedly over a span of 1 millisecond,
+/// as measured by the ARM generic timer. In that span, the counter must only
+/// ever advance, allowing for a single wrap-around, and its total advance
+/// must be within 10% of the time that passed on the generic timer. This
+/// catches a dead timer or a `TIMERUS_USEC_CFG_0` divider that doesn't match
+/// clk_m early on boot.
+pub fn self_test() -> Result<(), TimerError> {
+    let frequency = u64::from(CNTFRQ_EL0.get());
+    let span_ticks = frequency * u64::from(SELF_TEST_SPAN_US) / 1_000_000;
+    let start_ticks = CNTPCT_EL0.get();
+
+    // Sample the counter along with the generic timer until the span has passed.
+    let mut done = false;
+    let samples = iter::from_fn(|| {
+        if done {
+            return None;
+        }
+
+        let sample = get_microseconds();
+        let ticks = CNTPCT_EL0.get().wrapping_sub(start_ticks);
+        done = ticks >= span_ticks;
+
+        Some((sample, ticks))
+    });
+
+    check_self_test_samples(samples, frequency)
+}
+
+/// Checks samples of the microsecond counter against a reference clock for [`self_test`].
+///
+/// Every sample pairs a value of the microsecond counter with the ticks that
+/// the reference clock, which runs at `frequency` Hz, advanced by since the
+/// first sample. The samples are checked in order and the first failure is
+/// returned, without consuming the remaining samples.
+///
+/// [`self_test`]: fn.self_test.html
+fn check_self_test_samples<I>(samples: I, frequency: u64) -> Result<(), TimerError>
+where
+    I: IntoIterator<Item = (u32, u64)>,
+{
+    let mut samples = samples.into_iter();
+    let (start, _) = samples.next().ok_or(TimerError::Stuck)?;
+
+    let mut previous = start;
+    let mut elapsed_ticks = 0;
+    for (current, ticks) in samples {
+        // The counter advances by far less than half of its range during
+        // the test, so any larger difference means that it went backwards.
+        if current.wrapping_sub(previous) > u32::max_value() / 2 {
+            return Err(TimerError::NotMonotonic);
+        }
+
+        previous = current;
+        elapsed_ticks = ticks;
+    }
+
+    // Determine the time that passed according to the reference clock.
+    let expected = elapsed_ticks.saturating_mul(1_000_000).checked_div(frequency).unwrap_or(0);
+    let tolerance = expected * u64::from(SELF_TEST_TOLERANCE) / 100;
+
+    let elapsed = u64::from(previous.wrapping_sub(start));
+    if elapsed == 0 {
+        Err(TimerError::Stuck)
+    } else if elapsed + tolerance < expected || elapsed > expected + tolerance {
+        Err(TimerError::WrongRate)
+    } else {
+        Ok(())
+    }
+}
+
+/// A measurement of the microsecond counter, used for measuring elapsed time.
+///
+/// The counter is 32 bits wide and wraps around roughly every 71 minutes.
+/// Elapsed times are computed with wrapping arithmetic, so they are correct
+/// across a wrap-around, as long as less than a full period has passed.
+///
+/// ```no_run
+/// use libtegra::{spi::{Spi, SpiError}, timer::Instant};
+///
+/// fn timed_send(spi: &Spi, data: &[u8]) -> Result<u32, SpiError> {
+///     let start = Instant::now();
+///     spi.send(data)?;
+///
+///     Ok(start.elapsed_us())
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Instant(u32);
+
+impl Instant {
+    /// Takes a measurement of the microsecond counter.
+    #[inline]
+    pub fn now() -> Self {
+        Instant(get_microseconds())
+    }
+
+    /// Gets the time in microseconds that has passed since the measurement was taken.
+    #[inline]
+    pub fn elapsed_us(&self) -> u32 {
+        get_microseconds().wrapping_sub(self.0)
+    }
+
+    /// Gets the time in microseconds that has passed between an earlier measurement and this one.
+    #[inline]
+    pub fn duration_since_us(&self, earlier: Instant) -> u32 {
+        self.0.wrapping_sub(earlier.0)
+    }
+
+    /// Gets the time in microseconds that has passed between an earlier measurement and this one.
+    ///
+    /// Unlike [`Instant::duration_since_us`], this yields `0` rather than a
+    /// wrapped-around value if `earlier` was actually taken after this one.
+    /// A measurement counts as later if it is less than half the counter range,
+    /// i.e. roughly 35 minutes, ahead.
+    ///
+    /// [`Instant::duration_since_us`]: struct.Instant.html#method.duration_since_us
+    #[inline]
+    pub fn saturating_duration_since_us(&self, earlier: Instant) -> u32 {
+        let duration = self.0.wrapping_sub(earlier.0);
+
+        if duration > u32::max_value() / 2 {
+            0
+        } else {
+            duration
+        }
+    }
+}
+
+/// A stopwatch for benchmarking consecutive sections of code.
+///
+/// Every lap is timed from the start of the stopwatch, so the durations of
+/// the individual sections are the differences between consecutive laps.
+/// Like [`Instant`], this accounts for the microsecond counter wrapping around.
+///
+/// ```no_run
+/// use libtegra::{spi::Spi, timer::Stopwatch};
+///
+/// let stopwatch = Stopwatch::start();
+///
+/// Spi::SPI4.init();
+/// let init = stopwatch.lap();
+///
+/// let _ = Spi::SPI4.send(&[0x9F]);
+/// let first_transfer = stopwatch.stop() - init;
+/// ```
+///
+/// [`Instant`]: struct.Instant.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Stopwatch {
+    /// The time at which the stopwatch was started.
+    start: Instant,
+}
+
+impl Stopwatch {
+    /// Starts a new stopwatch.
+    #[inline]
+    pub fn start() -> Self {
+        Stopwatch { start: Instant::now() }
+    }
+
+    /// Takes a lap, i.e. gets the time in microseconds that has passed since the start.
+    #[inline]
+    pub fn lap(&self) -> u32 {
+        self.start.elapsed_us()
+    }
+
+    /// Stops the stopwatch and gets the time in microseconds that has passed since the start.
+    #[inline]
+    pub fn stop(self) -> u32 {
+        self.start.elapsed_us()
+    }
+}
+
+/// A provider of delays for generic device drivers.
+///
+/// With the `embedded-hal` feature enabled, this implements the `DelayNs`
+/// trait by delegating to [`nsleep`], [`usleep`] and [`msleep`].
+///
+/// ```ignore
+/// use embedded_hal::delay::DelayNs;
+/// use libtegra::timer::Delay;
+///
+/// fn reset_device<D: DelayNs>(delay: &mut D) {
+///     // Hold the device in reset for at least 10 microseconds.
+///     delay.delay_us(10);
+/// }
+///
+/// reset_device(&mut Delay);
+/// ```
+///
+/// [`nsleep`]: fn.nsleep.html
+/// [`usleep`]: fn.usleep.html
+/// [`msleep`]: fn.msleep.html
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Delay;
+
+/// A deadline on the microsecond counter, used for bounding polling loops.
+///
+/// Like [`Instant`], this correctly handles the counter wrapping around,
+/// so a timeout that is started close to the end of the counter range
+/// doesn't expire prematurely.
+///
+/// ```no_run
+/// use libtegra::timer::Timeout;
+///
+/// fn wait_for(flag: impl Fn() -> bool) -> bool {
+///     let timeout = Timeout::after_us(1000);
+///
+///     while !flag() && !timeout.expired() {}
+///
+///     flag()
+/// }
+/// ```
+///
+/// [`Instant`]: struct.Instant.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timeout {
+    /// The time at which the timeout was started.
+    start: Instant,
+    /// The duration of the timeout in microseconds.
+    duration: u32,
+}
+
+impl Timeout {
+    /// Starts a timeout that expires after a given duration in microseconds.
+    #[inline]
+    pub fn after_us(duration: u32) -> Self {
+        Timeout {
+            start: Instant::now(),
+            duration,
+        }
+    }
+
+    /// Whether the duration of the timeout has passed.
+    #[inline]
+    pub fn expired(&self) -> bool {
+        self.expired_at(Instant::now())
+    }
+
+    /// Whether the duration of the timeout has passed at a given measurement.
+    #[inline]
+    fn expired_at(&self, now: Instant) -> bool {
+        now.duration_since_us(self.start) > self.duration
+    }
+}
+
+/// A non-blocking countdown on the microsecond counter.
+///
+/// Unlike [`Timeout`], this can be restarted and is polled through
+/// [`CountDown::wait`], which never blocks. It uses the same deadline
+/// math, so it is safe across the wrap-around of the microsecond counter.
+/// With the `embedded-hal-02` feature enabled, this implements the
+/// `CountDown` trait of `embedded-hal` 0.2 for drivers that expect it.
+///
+/// ```no_run
+/// use libtegra::timer::CountDown;
+///
+/// let mut countdown = CountDown::new();
+/// countdown.start(500);
+///
+/// while countdown.wait().is_err() {
+///     // Do some work in the meantime.
+/// }
+/// ```
+///
+/// NOTE: Once the countdown has been observed to be over, this is latched,
+/// so it keeps reporting so until it is restarted.
+///
+/// [`Timeout`]: struct.Timeout.html
+/// [`CountDown::wait`]: struct.CountDown.html#method.wait
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CountDown {
+    /// The deadline of the countdown, if it is still running.
+    deadline: Option<Timeout>,
+}
+
+impl CountDown {
+    /// Creates a new countdown that isn't running.
+    pub const fn new() -> Self {
+        CountDown { deadline: None }
+    }
+
+    /// Starts the countdown over a given duration in microseconds.
+    ///
+    /// A running countdown is restarted.
+    #[inline]
+    pub fn start(&mut self, duration_us: u32) {
+        self.deadline = Some(Timeout::after_us(duration_us));
+    }
+
+    /// Checks whether the countdown is over, without blocking.
+    ///
+    /// While the duration hasn't passed yet, [`nb::Error::WouldBlock`] is
+    /// returned. A countdown that was never started is considered over.
+    ///
+    /// [`nb::Error::WouldBlock`]: https://docs.rs/nb/0.1/nb/enum.Error.html#variant.WouldBlock
+    pub fn wait(&mut self) -> nb::Result<(), Void> {
+        self.wait_at(Instant::now())
+    }
+
+    /// Checks whether the countdown is over at a given measurement.
+    fn wait_at(&mut self, now: Instant) -> nb::Result<(), Void> {
+        match self.deadline {
+            Some(timeout) if !timeout.expired_at(now) => Err(nb::Error::WouldBlock),
+            _ => {
+                self.deadline = None;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Polls a condition until it holds or a given time in microseconds has passed.
+///
+/// The condition is checked once more after the deadline has passed, so
+/// it is never reported as timed out if it was met in the meantime, e.g.
+/// while the caller was interrupted. If it still doesn't hold, [`TimeoutError`]
+/// is returned. The deadline is computed through [`Timeout`], so it is safe
+/// across the wrap-around of the microsecond counter.
+///
+/// [`TimeoutError`]: struct.TimeoutError.html
+/// [`Timeout`]: struct.Timeout.html
+pub fn wait_until<F: FnMut() -> bool>(mut cond: F, timeout_us: u32) -> Result<(), TimeoutError> {
+    let timeout = Timeout::after_us(timeout_us);
+
+    loop {
+        let expired = timeout.expired();
+
+        if cond() {
+            return Ok(());
+        }
+
+        if expired {
+            return Err(TimeoutError);
+        }
+    }
+}
+
+/// Polls a register field until it holds a given value or a given time in microseconds has passed.
+///
+/// This is a convenience over [`wait_until`] for the common case of waiting
+/// for the hardware to reflect a change, such as a busy or flush bit clearing.
+///
+/// ```no_run
+/// use libtegra::{spi::{SPI_1_REGISTERS, SPI_TRANSFER_STATUS_0}, timer::wait_for_field};
+///
+/// let controller = unsafe { &*SPI_1_REGISTERS };
+///
+/// // Wait up to a millisecond for the transfer to complete.
+/// wait_for_field(&controller.SPI_TRANSFER_STATUS_0, SPI_TRANSFER_STATUS_0::RDY, 1, 1000)
+///     .expect("Transfer timed out!");
+/// ```
+///
+/// [`wait_until`]: fn.wait_until.html
+pub fn wait_for_field<R: RegisterLongName>(
+    register: &ReadWrite<u32, R>,
+    field: Field<u32, R>,
+    value: u32,
+    timeout_us: u32,
+) -> Result<(), TimeoutError> {
+    wait_until(|| register.read(field) == value, timeout_us)
+}
+
+/// Representation of the Real-Time Clock.
+///
+/// The RTC counts the seconds and the milliseconds since it was last set
+/// in two separate registers. It keeps running across resets and while the
+/// rest of the system is powered off.
+///
+/// ```no_run
+/// use libtegra::timer::Rtc;
+///
+/// let start = Rtc::RTC.now_ms();
+/// // Do some work.
+/// let elapsed = Rtc::RTC.now_ms() - start;
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rtc {
+    /// A pointer to the RTC registers.
+    registers: *const rtc::Registers,
+}
+
+impl Rtc {
+    /// Representation of the RTC.
+    pub const RTC: Self = Rtc {
+        registers: rtc::REGISTERS,
+    };
+
+    /// Reads the current time in seconds.
+    #[inline]
+    pub fn seconds(&self) -> u32 {
+        let rtc = unsafe { &*self.registers };
+
+        rtc.APBDEV_RTC_SECONDS_0.get()
+    }
+
+    /// Reads the milliseconds within the current second.
+    ///
+    /// NOTE: This takes a snapshot of the seconds into the shadow register
+    /// that [`Rtc::now_ms`] relies on. Use [`Rtc::now_ms`] to read the full
+    /// time, rather than combining this with [`Rtc::seconds`].
+    ///
+    /// [`Rtc::now_ms`]: struct.Rtc.html#method.now_ms
+    /// [`Rtc::seconds`]: struct.Rtc.html#method.seconds
+    #[inline]
+    pub fn milliseconds(&self) -> u32 {
+        let rtc = unsafe { &*self.registers };
+
+        rtc.APBDEV_RTC_MILLI_SECONDS_0.read(rtc::APBDEV_RTC_MILLI_SECONDS_0::MILLI_SECONDS)
+    }
+
+    /// Reads the current time in milliseconds as a 64-bit value.
+    ///
+    /// The RTC keeps the seconds and the milliseconds in two separate registers.
+    /// Reading them one after another is prone to a carry glitch, where the
+    /// milliseconds wrap around in between the two reads. To avoid this, the
+    /// milliseconds are read first, which latches the seconds counter into the
+    /// `APBDEV_RTC_SHADOW_SECONDS_0` register, and the seconds are then taken
+    /// from the shadow register.
+    #[inline]
+    pub fn now_ms(&self) -> u64 {
+        let rtc = unsafe { &*self.registers };
+
+        // Reading MILLI_SECONDS takes a snapshot of SECONDS into SHADOW_SECONDS.
+        let milliseconds = self.milliseconds();
+        let seconds = rtc.APBDEV_RTC_SHADOW_SECONDS_0.get();
+
+        u64::from(seconds) * 1000 + u64::from(milliseconds)
+    }
+}
 
 /// Reads the current time in seconds.
+///
+/// This is a shorthand for [`Rtc::seconds`].
+///
+/// [`Rtc::seconds`]: struct.Rtc.html#method.seconds
 #[inline]
 pub fn get_seconds() -> u32 {
-    unsafe { (*rtc::REGISTERS).APBDEV_RTC_SECONDS_0.get() }
+    Rtc::RTC.seconds()
 }
 
 /// Reads the current time in milliseconds.
+///
+/// NOTE: The value wraps around roughly every 49 days of RTC time. Prefer
+/// [`get_milliseconds_64`] where this matters.
+///
+/// [`get_milliseconds_64`]: fn.get_milliseconds_64.html
 #[inline]
 pub fn get_milliseconds() -> u32 {
-    let rtc = unsafe { &*rtc::REGISTERS };
+    get_milliseconds_64() as u32
+}
 
-    rtc.APBDEV_RTC_MILLI_SECONDS_0.get() + (rtc.APBDEV_RTC_SHADOW_SECONDS_0.get() * 1000)
+/// Reads the current time in milliseconds as a 64-bit value.
+///
+/// This is a shorthand for [`Rtc::now_ms`], which describes how the
+/// seconds and the milliseconds are read without a carry glitch.
+///
+/// [`Rtc::now_ms`]: struct.Rtc.html#method.now_ms
+#[inline]
+pub fn get_milliseconds_64() -> u64 {
+    Rtc::RTC.now_ms()
 }
 
 /// Reads the current time in microseconds.
+///
+/// This is the value of `TIMERUS_CNTR_1US_0`, which counts up from boot
+/// and wraps around roughly every 71 minutes. For measuring elapsed time
+/// and bounding polling loops, prefer [`Instant`] and [`Timeout`], which
+/// account for the wrap-around. For timestamps that must not wrap around,
+/// use [`monotonic_us`], which extends this counter to 64 bits.
+///
+/// NOTE: With the `mock` feature, or in tests, the software counter of
+/// the [`mock`] module is read instead.
+///
+/// [`Instant`]: struct.Instant.html
+/// [`Timeout`]: struct.Timeout.html
+/// [`monotonic_us`]: fn.monotonic_us.html
+/// [`mock`]: mock/index.html
 #[inline]
 pub fn get_microseconds() -> u32 {
-    unsafe { (*timerus::REGISTERS).TIMERUS_CNTR_1US_0.get() }
+    match () {
+        #[cfg(not(any(test, feature = "mock")))]
+        () => unsafe { (*timerus::REGISTERS).TIMERUS_CNTR_1US_0.get() },
+        #[cfg(any(test, feature = "mock"))]
+        () => mock::read_microseconds(),
+    }
+}
+
+/// Reads the current time in microseconds as a 64-bit value that never wraps around.
+///
+/// This is the wide counter of this module. It is based on `TIMERUS_CNTR_1US_0`,
+/// so its lower 32 bits always match [`get_microseconds`]. The upper 32 bits are
+/// maintained in software and are incremented whenever the hardware counter
+/// is found to be smaller than on the previous call.
+///
+/// NOTE: A wrap-around can only be detected if this function is called at
+/// least once per wrap period of the hardware counter, i.e. every 71 minutes.
+/// Further, the software state is not synchronized, so this must not be called
+/// concurrently from multiple cores or from interrupt handlers.
+///
+/// [`get_microseconds`]: fn.get_microseconds.html
+pub fn monotonic_us() -> u64 {
+    let low = get_microseconds();
+
+    let previous_high = MONOTONIC_HIGH.load(Ordering::Relaxed);
+    let high = extend_high(low, MONOTONIC_LOW.load(Ordering::Relaxed), previous_high);
+    if high != previous_high {
+        MONOTONIC_HIGH.store(high, Ordering::Relaxed);
+    }
+    MONOTONIC_LOW.store(low, Ordering::Relaxed);
+
+    (u64::from(high) << 32) | u64::from(low)
+}
+
+/// Computes the upper word of [`monotonic_us`] from a new and the previous counter value.
+///
+/// [`monotonic_us`]: fn.monotonic_us.html
+#[inline]
+fn extend_high(low: u32, previous_low: u32, high: u32) -> u32 {
+    // The hardware counter went backwards, so it must have wrapped around.
+    if low < previous_low {
+        high.wrapping_add(1)
+    } else {
+        high
+    }
+}
+
+/// Reads the current time in microseconds along with the level of a [`Gpio`].
+///
+/// This is useful for timestamping external events, such as the assertion
+/// of a device's interrupt line. Both values are read back-to-back with
+/// IRQs and FIQs masked to minimize the skew between them. Note that true
+/// simultaneity is not possible, as they are still two separate reads.
+///
+/// NOTE: Interrupts are only masked on AArch64. On other architectures,
+/// such as when testing on the host, the values are read without masking.
+///
+/// [`Gpio`]: ../gpio/struct.Gpio.html
+pub fn timestamp_gpio(gpio: &Gpio) -> (u32, bool) {
+    match () {
+        #[cfg(target_arch = "aarch64")]
+        () => {
+            // Mask interrupts and save the previous state.
+            let daif = DAIF.get();
+            DAIF.modify(DAIF::I::Masked + DAIF::F::Masked);
+
+            let sample = timestamp_level(|| gpio.is_high());
+
+            // Restore the previous interrupt state.
+            DAIF.set(daif);
+
+            sample
+        }
+
+        #[cfg(not(target_arch = "aarch64"))]
+        () => timestamp_level(|| gpio.is_high()),
+    }
+}
+
+/// Reads the current time in microseconds, immediately followed by a level.
+///
+/// The timestamp is taken first, so it never postdates the level.
+#[inline]
+fn timestamp_level<F: FnOnce() -> bool>(level: F) -> (u32, bool) {
+    let timestamp = get_microseconds();
+
+    (timestamp, level())
 }
 
 /// Sleeps for a given duration in seconds.
@@ -66,17 +922,815 @@ pub fn sleep(duration: u32) {
 }
 
 /// Sleeps for a given duration in milliseconds.
+///
+/// This is built on the microsecond counter, just like [`usleep`].
+/// The duration is converted to microseconds without overflowing, so
+/// the full range of `duration` is supported and callers don't need to
+/// resort to `usleep(ms * 1000)`.
+///
+/// [`usleep`]: fn.usleep.html
 #[inline]
 pub fn msleep(duration: u32) {
-    let start = get_milliseconds();
-
-    while (get_milliseconds() - start) <= duration {}
+    spin_us(u64::from(duration) * 1000);
 }
 
 /// Sleeps for a given duration in microseconds.
+///
+/// The elapsed time is computed with wrapping arithmetic, so a delay that
+/// straddles the wrap-around of the microsecond counter ends in time. Long
+/// delays are split into chunks of at most half the counter range, so that
+/// the elapsed time can always be told apart from a wrapped-around counter.
 #[inline]
 pub fn usleep(duration: u32) {
-    let start = get_microseconds();
+    spin_us(u64::from(duration));
+}
+
+/// Busy-waits until more than a given duration in microseconds has passed.
+#[inline]
+fn spin_us(duration: u64) {
+    let mut deadline = SleepDeadline::new(get_microseconds(), duration);
+
+    while !deadline.is_over_at(get_microseconds()) {}
+}
+
+/// The deadline of a sleep on the microsecond counter.
+///
+/// The sleep is split into chunks of at most [`MAX_SLEEP_CHUNK_US`], which
+/// start one after another. Within a chunk, the wrapping difference to its
+/// start can't exceed half the counter range, so it is never mistaken for a
+/// wrapped-around counter.
+///
+/// [`MAX_SLEEP_CHUNK_US`]: constant.MAX_SLEEP_CHUNK_US.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct SleepDeadline {
+    /// The counter value at which the current chunk started.
+    start: u32,
+    /// The microseconds left to wait for, starting from the current chunk.
+    remaining: u64,
+}
+
+impl SleepDeadline {
+    /// Creates the deadline of a sleep that starts at the counter value `now`.
+    fn new(now: u32, duration: u64) -> Self {
+        SleepDeadline {
+            start: now,
+            remaining: duration,
+        }
+    }
+
+    /// Checks whether more than the duration of the sleep has passed at the counter value `now`.
+    ///
+    /// NOTE: This must be checked at least once per [`MAX_SLEEP_CHUNK_US`],
+    /// otherwise the end of a chunk may be missed.
+    ///
+    /// [`MAX_SLEEP_CHUNK_US`]: constant.MAX_SLEEP_CHUNK_US.html
+    fn is_over_at(&mut self, now: u32) -> bool {
+        loop {
+            let elapsed = now.wrapping_sub(self.start);
+
+            // The last chunk is over once its duration has been exceeded.
+            if self.remaining <= u64::from(MAX_SLEEP_CHUNK_US) {
+                return u64::from(elapsed) > self.remaining;
+            }
+
+            if elapsed < MAX_SLEEP_CHUNK_US {
+                return false;
+            }
+
+            // Move on to the next chunk, which starts where this one ended.
+            self.start = self.start.wrapping_add(MAX_SLEEP_CHUNK_US);
+            self.remaining -= u64::from(MAX_SLEEP_CHUNK_US);
+        }
+    }
+}
+
+/// Sleeps for a given duration in nanoseconds.
+///
+/// Durations of at least a microsecond are rounded up to whole microseconds
+/// and slept through [`usleep`]. The microsecond counter can't represent
+/// shorter durations, so these are busy-waited on the counter of the ARM
+/// generic timer instead, whose resolution is a single tick of `CNTFRQ_EL0`,
+/// e.g. about 52 nanoseconds at 19.2 MHz.
+///
+/// NOTE: Sub-microsecond delays are only accurate up to the resolution of
+/// the generic timer, plus the overhead of reading it, so durations below
+/// roughly 100 nanoseconds are overshot. They are never cut short, though.
+///
+/// [`usleep`]: fn.usleep.html
+#[inline]
+pub fn nsleep(duration: u32) {
+    if duration >= 1000 {
+        let micros = duration / 1000 + (duration % 1000 != 0) as u32;
+
+        usleep(micros);
+    } else {
+        let frequency = u64::from(CNTFRQ_EL0.get());
+        let ticks = (u64::from(duration) * frequency + 999_999_999) / 1_000_000_000;
+        let start = CNTPCT_EL0.get();
+
+        while CNTPCT_EL0.get().wrapping_sub(start) < ticks.max(1) {}
+    }
+}
+
+/// Representation of a TMR timer.
+///
+/// NOTE: Instances of this structure should never be created manually.
+/// Refer to the public constants this structure holds, which represent
+/// the timers 0 through 9.
+///
+/// The timers count down from a trigger value in microseconds and raise an
+/// interrupt when they expire. Routing the interrupt to a handler is up to
+/// the user. TMR5 through TMR9 serve as the time bases of the [`Watchdog`]s
+/// and must not be used while the respective watchdog is running.
+///
+/// ```no_run
+/// use libtegra::timer::Timer;
+///
+/// // Tick every millisecond.
+/// Timer::TMR1.set_periodic(1000).unwrap();
+///
+/// loop {
+///     if Timer::TMR1.is_expired() {
+///         Timer::TMR1.clear_interrupt();
+///
+///         // Do some periodic work.
+///     }
+/// }
+/// ```
+///
+/// [`Watchdog`]: struct.Watchdog.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timer {
+    /// A pointer to the [`Registers`] of the timer.
+    ///
+    /// [`Registers`]: tmr/struct.Registers.html
+    registers: *const tmr::Registers,
+    /// The number of the timer.
+    index: u32,
+}
+
+// Definitions of known timers.
+
+impl Timer {
+    /// Representation of TMR0.
+    pub const TMR0: Self = Timer {
+        registers: tmr::TMR_0_REGISTERS,
+        index: 0,
+    };
+
+    /// Representation of TMR1.
+    pub const TMR1: Self = Timer {
+        registers: tmr::TMR_1_REGISTERS,
+        index: 1,
+    };
+
+    /// Representation of TMR2.
+    pub const TMR2: Self = Timer {
+        registers: tmr::TMR_2_REGISTERS,
+        index: 2,
+    };
+
+    /// Representation of TMR3.
+    pub const TMR3: Self = Timer {
+        registers: tmr::TMR_3_REGISTERS,
+        index: 3,
+    };
+
+    /// Representation of TMR4.
+    pub const TMR4: Self = Timer {
+        registers: tmr::TMR_4_REGISTERS,
+        index: 4,
+    };
+
+    /// Representation of TMR5.
+    pub const TMR5: Self = Timer {
+        registers: tmr::TMR_5_REGISTERS,
+        index: 5,
+    };
+
+    /// Representation of TMR6.
+    pub const TMR6: Self = Timer {
+        registers: tmr::TMR_6_REGISTERS,
+        index: 6,
+    };
+
+    /// Representation of TMR7.
+    pub const TMR7: Self = Timer {
+        registers: tmr::TMR_7_REGISTERS,
+        index: 7,
+    };
+
+    /// Representation of TMR8.
+    pub const TMR8: Self = Timer {
+        registers: tmr::TMR_8_REGISTERS,
+        index: 8,
+    };
+
+    /// Representation of TMR9.
+    pub const TMR9: Self = Timer {
+        registers: tmr::TMR_9_REGISTERS,
+        index: 9,
+    };
+}
+
+impl Timer {
+    /// The longest duration in microseconds a timer can be armed with.
+    pub const MAX_DURATION: u32 = MAX_TIMER_TRIGGER + 1;
+
+    /// Arms the timer with a given duration in microseconds.
+    fn arm(&self, duration: u32, periodic: bool) -> Result<(), InvalidDurationError> {
+        if duration == 0 || duration > Self::MAX_DURATION {
+            return Err(InvalidDurationError);
+        }
+
+        let timer = unsafe { &*self.registers };
+
+        // Stop the timer and clear a pending interrupt.
+        self.clear();
+
+        // The timer expires one microsecond after it reaches 0.
+        timer.TIMER_TMR_PTV_0.write(
+            tmr::TIMER_TMR_PTV_0::EN::SET
+            + tmr::TIMER_TMR_PTV_0::PER.val(periodic as u32)
+            + tmr::TIMER_TMR_PTV_0::TMR_PTV.val(duration - 1)
+        );
+
+        Ok(())
+    }
+
+    /// Arms the timer to expire periodically, every given duration in microseconds.
+    ///
+    /// NOTE: Durations must be in the range of 1 to [`MAX_DURATION`] microseconds,
+    /// i.e. roughly 9 minutes at most. Other durations are rejected with an
+    /// [`InvalidDurationError`] and leave the timer untouched.
+    ///
+    /// [`MAX_DURATION`]: #associatedconstant.MAX_DURATION
+    /// [`InvalidDurationError`]: struct.InvalidDurationError.html
+    pub fn set_periodic(&self, duration: u32) -> Result<(), InvalidDurationError> {
+        self.arm(duration, true)
+    }
+
+    /// Arms the timer to expire once, after a given duration in microseconds.
+    ///
+    /// NOTE: Durations must be in the range of 1 to [`MAX_DURATION`] microseconds,
+    /// i.e. roughly 9 minutes at most. Other durations are rejected with an
+    /// [`InvalidDurationError`] and leave the timer untouched.
+    ///
+    /// [`MAX_DURATION`]: #associatedconstant.MAX_DURATION
+    /// [`InvalidDurationError`]: struct.InvalidDurationError.html
+    pub fn set_oneshot(&self, duration: u32) -> Result<(), InvalidDurationError> {
+        self.arm(duration, false)
+    }
+
+    /// Whether the timer has expired and its interrupt is pending.
+    pub fn is_expired(&self) -> bool {
+        let shared = unsafe { &*tmr::SHARED_REGISTERS };
+
+        shared.TIMER_TMR_SHARED_INTR_STATUS_0.read(tmr::TIMER_TMR_SHARED_INTR_STATUS_0::INTR_STATUS)
+            & (1 << self.index)
+            != 0
+    }
+
+    /// Acknowledges the interrupt of the timer.
+    ///
+    /// This writes `1` to the `INTR_CLR` bit of `TIMER_TMR_PCR_0`, which
+    /// clears the bit of the timer in `TIMER_TMR_SHARED_INTR_STATUS_0` and
+    /// deasserts the interrupt line. An interrupt handler must do this before
+    /// returning, otherwise the interrupt fires again immediately.
+    pub fn clear_interrupt(&self) {
+        let timer = unsafe { &*self.registers };
+
+        timer.TIMER_TMR_PCR_0.write(tmr::TIMER_TMR_PCR_0::INTR_CLR::SET);
+    }
+
+    /// Stops the timer.
+    pub fn stop(&self) {
+        let timer = unsafe { &*self.registers };
+
+        timer.TIMER_TMR_PTV_0.set(0);
+    }
+
+    /// Registers a callback that is invoked by [`Timer::handle_interrupt`].
+    ///
+    /// Passing `None` removes a previously registered callback.
+    ///
+    /// [`Timer::handle_interrupt`]: struct.Timer.html#method.handle_interrupt
+    pub fn set_callback(&self, callback: Option<fn()>) {
+        let callback = callback.map_or(0, |callback| callback as usize);
+
+        TIMER_CALLBACKS[self.index as usize].store(callback, Ordering::Release);
+    }
+
+    /// Handles a pending interrupt of the timer.
+    ///
+    /// If the timer has expired, its interrupt is acknowledged through
+    /// [`Timer::clear_interrupt`] and the callback registered through
+    /// [`Timer::set_callback`] is invoked. Returns whether the timer had
+    /// expired, so a handler that is shared by multiple timers can tell
+    /// which of them raised the interrupt.
+    ///
+    /// NOTE: The timers have no separate interrupt enable bit, an armed timer
+    /// raises its interrupt whenever it expires. Routing the respective
+    /// interrupt line to a handler that calls this method is up to the user.
+    ///
+    /// ```no_run
+    /// use libtegra::timer::Timer;
+    ///
+    /// fn tick() {
+    ///     // Do some periodic work.
+    /// }
+    ///
+    /// Timer::TMR1.set_callback(Some(tick));
+    /// Timer::TMR1.set_periodic(1000).unwrap();
+    ///
+    /// // In the interrupt handler of TMR1:
+    /// Timer::TMR1.handle_interrupt();
+    /// ```
+    ///
+    /// [`Timer::clear_interrupt`]: struct.Timer.html#method.clear_interrupt
+    /// [`Timer::set_callback`]: struct.Timer.html#method.set_callback
+    pub fn handle_interrupt(&self) -> bool {
+        if !self.is_expired() {
+            return false;
+        }
+
+        self.clear_interrupt();
+
+        let callback = TIMER_CALLBACKS[self.index as usize].load(Ordering::Acquire);
+        if callback != 0 {
+            // Only valid function pointers are stored by `set_callback`.
+            let callback: fn() = unsafe { mem::transmute(callback) };
+            callback();
+        }
+
+        true
+    }
+
+    /// Invokes a callback periodically, every given duration in microseconds.
+    ///
+    /// This registers the callback through [`Timer::set_callback`] and arms
+    /// the timer through [`Timer::set_periodic`], so its interrupt is raised
+    /// on every expiration. The callback is invoked from [`handle_timer_interrupts`]
+    /// or [`Timer::handle_interrupt`].
+    ///
+    /// NOTE: Routing the interrupt of the timer to a handler is up to the user,
+    /// i.e. the interrupt has to be enabled in the interrupt controller and
+    /// the handler has to be wired into the vector table.
+    ///
+    /// [`Timer::set_callback`]: struct.Timer.html#method.set_callback
+    /// [`Timer::set_periodic`]: struct.Timer.html#method.set_periodic
+    /// [`handle_timer_interrupts`]: fn.handle_timer_interrupts.html
+    /// [`Timer::handle_interrupt`]: struct.Timer.html#method.handle_interrupt
+    pub fn on_tick(&self, period_us: u32, callback: fn()) -> Result<(), InvalidDurationError> {
+        self.set_callback(Some(callback));
+        self.set_periodic(period_us)
+    }
+
+    /// Stops the timer and acknowledges a pending interrupt, returning it to its idle state.
+    pub fn clear(&self) {
+        self.stop();
+        self.clear_interrupt();
+    }
+}
+
+/// Handles the pending interrupts of all TMR timers with a registered callback.
+///
+/// This is meant to be called from the interrupt handler that the TMR
+/// interrupts are routed to. Every timer that has a callback registered
+/// through [`Timer::set_callback`] or [`Timer::on_tick`] is passed to
+/// [`Timer::handle_interrupt`], which acknowledges its interrupt and
+/// invokes the callback if it has expired. Timers without a callback are
+/// left alone, e.g. the time bases of running [`Watchdog`]s.
+///
+/// [`Timer::set_callback`]: struct.Timer.html#method.set_callback
+/// [`Timer::on_tick`]: struct.Timer.html#method.on_tick
+/// [`Timer::handle_interrupt`]: struct.Timer.html#method.handle_interrupt
+/// [`Watchdog`]: struct.Watchdog.html
+pub fn handle_timer_interrupts() {
+    let timers = [
+        Timer::TMR0,
+        Timer::TMR1,
+        Timer::TMR2,
+        Timer::TMR3,
+        Timer::TMR4,
+        Timer::TMR5,
+        Timer::TMR6,
+        Timer::TMR7,
+        Timer::TMR8,
+        Timer::TMR9,
+    ];
+
+    for timer in &timers {
+        if TIMER_CALLBACKS[timer.index as usize].load(Ordering::Acquire) != 0 {
+            timer.handle_interrupt();
+        }
+    }
+}
+
+/// Representation of a watchdog timer.
+///
+/// NOTE: Instances of this structure should never be created manually.
+/// Refer to the public constants this structure holds, which represent
+/// the watchdogs 0 through 4.
+///
+/// Every watchdog uses a TMR timer as its time base, which must not be
+/// used for anything else while the watchdog is running. WDT0 through
+/// WDT4 use TMR5 through TMR9 respectively.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Watchdog {
+    /// A pointer to the [`Registers`] of the watchdog.
+    ///
+    /// [`Registers`]: wdt/struct.Registers.html
+    registers: *const wdt::Registers,
+    /// A pointer to the [`Registers`] of the TMR timer that serves as the time base.
+    ///
+    /// [`Registers`]: tmr/struct.Registers.html
+    timer: *const tmr::Registers,
+    /// The number of the TMR timer that serves as the time base.
+    source: u32,
+}
+
+// Definitions of known watchdogs.
+
+impl Watchdog {
+    /// Representation of WDT0.
+    pub const WDT0: Self = Watchdog {
+        registers: wdt::WDT_0_REGISTERS,
+        timer: tmr::TMR_5_REGISTERS,
+        source: 5,
+    };
+
+    /// Representation of WDT1.
+    pub const WDT1: Self = Watchdog {
+        registers: wdt::WDT_1_REGISTERS,
+        timer: tmr::TMR_6_REGISTERS,
+        source: 6,
+    };
+
+    /// Representation of WDT2.
+    pub const WDT2: Self = Watchdog {
+        registers: wdt::WDT_2_REGISTERS,
+        timer: tmr::TMR_7_REGISTERS,
+        source: 7,
+    };
+
+    /// Representation of WDT3.
+    pub const WDT3: Self = Watchdog {
+        registers: wdt::WDT_3_REGISTERS,
+        timer: tmr::TMR_8_REGISTERS,
+        source: 8,
+    };
+
+    /// Representation of WDT4.
+    pub const WDT4: Self = Watchdog {
+        registers: wdt::WDT_4_REGISTERS,
+        timer: tmr::TMR_9_REGISTERS,
+        source: 9,
+    };
+}
+
+impl Watchdog {
+    /// Arms the watchdog to reset the system if it isn't petted within a given time in microseconds.
+    ///
+    /// The whole system is reset through the PMC once the watchdog expires.
+    /// The source timer expires every quarter of the timeout and the reset
+    /// happens on its fourth expiration. As the source timer runs freely,
+    /// the system is reset between three quarters of the timeout and the
+    /// full timeout after the last call to [`Watchdog::pet`].
+    ///
+    /// NOTE: Every quarter of the timeout must be in the range of 1 to
+    /// [`Timer::MAX_DURATION`] microseconds, so timeouts must be at least
+    /// 4 microseconds and at most roughly 35 minutes. Other timeouts are
+    /// rejected with an [`InvalidDurationError`] and leave the watchdog
+    /// untouched. Timeouts that are not a multiple of 4 are rounded down.
+    ///
+    /// After the reset, the PMC reports the watchdog as the reset source,
+    /// see [`Watchdog::caused_last_reset`].
+    ///
+    /// [`Watchdog::pet`]: struct.Watchdog.html#method.pet
+    /// [`Watchdog::caused_last_reset`]: struct.Watchdog.html#method.caused_last_reset
+    /// [`Timer::MAX_DURATION`]: struct.Timer.html#associatedconstant.MAX_DURATION
+    /// [`InvalidDurationError`]: struct.InvalidDurationError.html
+    pub fn start(&self, timeout_us: u32) -> Result<(), InvalidDurationError> {
+        let period = timeout_us / WATCHDOG_EXPIRATIONS;
+        if period == 0 || period > Timer::MAX_DURATION {
+            return Err(InvalidDurationError);
+        }
+
+        let watchdog = unsafe { &*self.registers };
+        let timer = unsafe { &*self.timer };
+
+        // Stop the watchdog, in case it is running already.
+        self.disable();
+
+        // Configure the source timer to expire periodically.
+        // Like for a Timer, it expires one microsecond after it reaches 0.
+        timer.TIMER_TMR_PTV_0.write(
+            tmr::TIMER_TMR_PTV_0::EN::SET
+            + tmr::TIMER_TMR_PTV_0::PER::SET
+            + tmr::TIMER_TMR_PTV_0::TMR_PTV.val(period - 1)
+        );
+
+        // Count every expiration of the source timer as a watchdog period.
+        // The watchdog escalates with every period and resets the system on
+        // the fourth one.
+        watchdog.TIMER_WDT_CONFIG_0.write(
+            wdt::TIMER_WDT_CONFIG_0::TIMER_SOURCE.val(self.source)
+            + wdt::TIMER_WDT_CONFIG_0::PERIOD.val(1)
+            + wdt::TIMER_WDT_CONFIG_0::PMC2CAR_RST_EN::SET
+        );
+
+        // Start the watchdog counter.
+        watchdog.TIMER_WDT_COMMAND_0.write(wdt::TIMER_WDT_COMMAND_0::START_COUNTER::SET);
+
+        Ok(())
+    }
+
+    /// Pets the watchdog, i.e. restarts its counter.
+    ///
+    /// The counter is restarted in a single write, without stopping the
+    /// watchdog in between, so there is no window in which it is disarmed.
+    pub fn pet(&self) {
+        let watchdog = unsafe { &*self.registers };
+
+        watchdog.TIMER_WDT_COMMAND_0.write(wdt::TIMER_WDT_COMMAND_0::START_COUNTER::SET);
+    }
+
+    /// Whether the last reset of the system was caused by a watchdog.
+    ///
+    /// The PMC records the source of the last reset in the `RST_SOURCE` field
+    /// of `APBDEV_PMC_RST_STATUS_0`. It reads `Watchdog` after a reset by any
+    /// of the watchdogs, as opposed to `PowerOnReset` after a cold boot.
+    pub fn caused_last_reset() -> bool {
+        let pmc = unsafe { &*pmc::REGISTERS };
+
+        pmc.APBDEV_PMC_RST_STATUS_0
+            .matches_all(pmc::APBDEV_PMC_RST_STATUS_0::RST_SOURCE::Watchdog)
+    }
+
+    /// Disarms the watchdog and stops its source timer.
+    pub fn disable(&self) {
+        let watchdog = unsafe { &*self.registers };
+        let timer = unsafe { &*self.timer };
+
+        // Unlock the watchdog and disable its counter.
+        watchdog
+            .TIMER_WDT_UNLOCK_PATTERN_0
+            .write(wdt::TIMER_WDT_UNLOCK_PATTERN_0::UNLOCK_PATTERN::Unlock);
+        watchdog.TIMER_WDT_COMMAND_0.write(wdt::TIMER_WDT_COMMAND_0::DISABLE_COUNTER::SET);
+
+        // Stop the source timer.
+        timer.TIMER_TMR_PTV_0.set(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Starts a countdown of a given duration at a given counter value.
+    fn countdown_at(start: u32, duration: u32) -> CountDown {
+        CountDown {
+            deadline: Some(Timeout {
+                start: Instant(start),
+                duration,
+            }),
+        }
+    }
+
+    #[test]
+    fn instant_durations_wrap_around() {
+        let earlier = Instant(u32::max_value() - 9);
+        let later = Instant(10);
+
+        assert_eq!(later.duration_since_us(earlier), 20);
+        assert_eq!(later.saturating_duration_since_us(earlier), 20);
+
+        // A measurement ahead of this one saturates to 0.
+        assert_eq!(earlier.saturating_duration_since_us(later), 0);
+        assert_eq!(earlier.duration_since_us(later), u32::max_value() - 19);
+    }
+
+    #[test]
+    fn saturation_starts_at_half_the_counter_range() {
+        let start = Instant(0);
+
+        let half = Instant(u32::max_value() / 2);
+        assert_eq!(half.saturating_duration_since_us(start), u32::max_value() / 2);
+
+        let beyond = Instant(u32::max_value() / 2 + 1);
+        assert_eq!(beyond.saturating_duration_since_us(start), 0);
+    }
+
+    #[test]
+    fn timeout_expires_across_wrap_around() {
+        let timeout = Timeout {
+            start: Instant(u32::max_value() - 99),
+            duration: 200,
+        };
+
+        // The counter wrapping around doesn't expire the timeout prematurely.
+        assert!(!timeout.expired_at(Instant(u32::max_value())));
+        assert!(!timeout.expired_at(Instant(0)));
+        assert!(!timeout.expired_at(Instant(100)));
+
+        // The timeout expires only once more than its duration has passed.
+        assert!(timeout.expired_at(Instant(101)));
+    }
+
+    #[test]
+    fn countdown_completes_across_wrap_around() {
+        let mut countdown = countdown_at(u32::max_value() - 4, 10);
+
+        assert_eq!(countdown.wait_at(Instant(u32::max_value())), Err(nb::Error::WouldBlock));
+        assert_eq!(countdown.wait_at(Instant(5)), Err(nb::Error::WouldBlock));
+        assert_eq!(countdown.wait_at(Instant(6)), Ok(()));
+
+        // The completion is latched, even if the counter has moved on since.
+        assert_eq!(countdown.wait_at(Instant(u32::max_value() - 4)), Ok(()));
+        assert_eq!(countdown.deadline, None);
+    }
+
+    #[test]
+    fn high_word_advances_on_wrap_around() {
+        assert_eq!(extend_high(0, 0, 0), 0);
+        assert_eq!(extend_high(1000, 10, 0), 0);
+        assert_eq!(extend_high(u32::max_value(), u32::max_value(), 3), 3);
+
+        // The counter wrapped around since the previous call.
+        assert_eq!(extend_high(5, u32::max_value(), 0), 1);
+        assert_eq!(extend_high(0, 1, 7), 8);
+
+        // The upper word wraps around itself after roughly 584,000 years.
+        assert_eq!(extend_high(0, 1, u32::max_value()), 0);
+    }
+
+    #[test]
+    fn idle_countdown_is_over() {
+        assert_eq!(CountDown::new().wait_at(Instant(0)), Ok(()));
+    }
+
+    #[test]
+    fn usleep_waits_on_the_software_counter() {
+        mock::set_microseconds(1000);
+
+        usleep(100);
+        assert!(get_microseconds() > 1100);
+
+        // Polling gives up once the deadline has passed.
+        assert_eq!(wait_until(|| false, 50), Err(TimeoutError));
+    }
+
+    /// Generates the samples of a self-test at 19.2 MHz, taken every 10 microseconds.
+    ///
+    /// The counter starts at `start` and advances at `rate` percent of the expected rate.
+    fn self_test_samples(start: u32, rate: u32) -> impl Iterator<Item = (u32, u64)> {
+        (0..=100).map(move |i| (start.wrapping_add(i * 10 * rate / 100), u64::from(i) * 192))
+    }
+
+    #[test]
+    fn self_test_accepts_a_healthy_counter() {
+        assert_eq!(check_self_test_samples(self_test_samples(0, 100), 19_200_000), Ok(()));
+        assert_eq!(check_self_test_samples(self_test_samples(0, 95), 19_200_000), Ok(()));
+        assert_eq!(check_self_test_samples(self_test_samples(0, 108), 19_200_000), Ok(()));
+
+        // A single wrap-around of the counter is fine.
+        let start = u32::max_value() - 499;
+        assert_eq!(check_self_test_samples(self_test_samples(start, 100), 19_200_000), Ok(()));
+    }
+
+    #[test]
+    fn self_test_detects_a_stuck_counter() {
+        assert_eq!(
+            check_self_test_samples(self_test_samples(1234, 0), 19_200_000),
+            Err(TimerError::Stuck)
+        );
+        assert_eq!(check_self_test_samples(iter::empty(), 19_200_000), Err(TimerError::Stuck));
+    }
+
+    #[test]
+    fn self_test_detects_a_wrong_rate() {
+        // The divider is set up for 12 MHz, but clk_m runs at 38.4 MHz or vice versa.
+        assert_eq!(
+            check_self_test_samples(self_test_samples(0, 320), 19_200_000),
+            Err(TimerError::WrongRate)
+        );
+        assert_eq!(
+            check_self_test_samples(self_test_samples(0, 31), 19_200_000),
+            Err(TimerError::WrongRate)
+        );
+
+        // Just outside of the tolerance.
+        assert_eq!(
+            check_self_test_samples(self_test_samples(0, 89), 19_200_000),
+            Err(TimerError::WrongRate)
+        );
+        assert_eq!(
+            check_self_test_samples(self_test_samples(0, 111), 19_200_000),
+            Err(TimerError::WrongRate)
+        );
+
+        // Without a reference rate, no rate is right.
+        assert_eq!(
+            check_self_test_samples(self_test_samples(0, 100), 0),
+            Err(TimerError::WrongRate)
+        );
+    }
+
+    #[test]
+    fn self_test_detects_a_counter_going_backwards() {
+        let samples = self_test_samples(1000, 100).map(|(us, ticks)| {
+            if ticks == 50 * 192 {
+                (us - 100, ticks)
+            } else {
+                (us, ticks)
+            }
+        });
+
+        assert_eq!(check_self_test_samples(samples, 19_200_000), Err(TimerError::NotMonotonic));
+    }
+
+    #[test]
+    fn sleep_deadline_crosses_the_wrap_around() {
+        let start = u32::max_value() - 9;
+        let mut deadline = SleepDeadline::new(start, 100);
+
+        assert!(!deadline.is_over_at(start));
+        assert!(!deadline.is_over_at(u32::max_value()));
+        assert!(!deadline.is_over_at(90));
+        assert!(deadline.is_over_at(91));
+    }
+
+    #[test]
+    fn long_sleeps_are_chunked_across_wrap_arounds() {
+        // Sleep for more than twice the counter range, starting close to its end.
+        let duration = 2 * (u64::from(u32::max_value()) + 1) + 1000;
+        let start = u32::max_value() - 99;
+        let mut deadline = SleepDeadline::new(start, duration);
+
+        // Poll a few times per chunk, following the counter across its wrap-arounds.
+        let step = MAX_SLEEP_CHUNK_US / 3;
+        let mut elapsed = 0u64;
+        while elapsed + u64::from(step) <= duration {
+            elapsed += u64::from(step);
+            assert!(!deadline.is_over_at(start.wrapping_add(elapsed as u32)));
+        }
+
+        assert!(!deadline.is_over_at(start.wrapping_add(duration as u32)));
+        assert!(deadline.is_over_at(start.wrapping_add(duration as u32 + 1)));
+    }
+
+    #[test]
+    fn usleep_crosses_the_wrap_around() {
+        mock::set_microseconds(u32::max_value() - 49);
+        mock::set_step(7);
+
+        usleep(100);
+
+        // The counter wrapped around and the sleep still ended in time.
+        let now = get_microseconds();
+        assert!(now > 50 && now < 50 + 3 * 7);
+
+        mock::set_step(1);
+    }
+
+    #[test]
+    fn rtc_time_is_taken_from_the_shadow_seconds() {
+        // Emulate the RTC registers in host memory, with the shadow register
+        // holding the seconds at the time MILLI_SECONDS was last read.
+        let mut registers = [0u32; mem::size_of::<rtc::Registers>() / 4];
+        registers[0x08 / 4] = 13;
+        registers[0x0C / 4] = 12;
+        registers[0x10 / 4] = 999;
+
+        let rtc = Rtc {
+            registers: registers.as_ptr() as *const rtc::Registers,
+        };
+
+        assert_eq!(rtc.seconds(), 13);
+        assert_eq!(rtc.milliseconds(), 999);
+        assert_eq!(rtc.now_ms(), 12_999);
+    }
+
+    #[test]
+    fn timestamp_is_taken_before_the_level() {
+        mock::set_microseconds(100);
+
+        let sample = timestamp_level(|| {
+            // The level is read after the timestamp.
+            assert_eq!(get_microseconds(), 101);
+
+            true
+        });
+
+        assert_eq!(sample, (100, true));
+    }
+
+    #[test]
+    fn timestamp_is_paired_with_the_level() {
+        mock::set_microseconds(u32::max_value());
 
-    while (get_microseconds() - start) <= duration {}
+        assert_eq!(timestamp_level(|| false), (u32::max_value(), false));
+        assert_eq!(timestamp_level(|| true), (0, true));
+    }
 }
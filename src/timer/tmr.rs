@@ -0,0 +1,91 @@
+//! Abstractions over the NVIDIA Generic Timer registers.
+//!
+//! See Chapter 8 in the Tegra X1 Technical Reference Manual for details.
+//!
+//! # Description
+//!
+//! The ten TMR timers count down from a programmable trigger value at the
+//! rate of the fixed time base, i.e. once per microsecond, and raise an
+//! interrupt when they expire. They can either run once or periodically.
+
+use register::{mmio::ReadWrite, register_bitfields, register_structs};
+
+use crate::memory_map::TMR;
+
+/// A pointer to the TMR0 register block that can be accessed by dereferencing it.
+pub const TMR_0_REGISTERS: *const Registers = (TMR + 0x88) as *const Registers;
+/// A pointer to the TMR1 register block that can be accessed by dereferencing it.
+pub const TMR_1_REGISTERS: *const Registers = (TMR + 0x00) as *const Registers;
+/// A pointer to the TMR2 register block that can be accessed by dereferencing it.
+pub const TMR_2_REGISTERS: *const Registers = (TMR + 0x08) as *const Registers;
+/// A pointer to the TMR3 register block that can be accessed by dereferencing it.
+pub const TMR_3_REGISTERS: *const Registers = (TMR + 0x50) as *const Registers;
+/// A pointer to the TMR4 register block that can be accessed by dereferencing it.
+pub const TMR_4_REGISTERS: *const Registers = (TMR + 0x58) as *const Registers;
+/// A pointer to the TMR5 register block that can be accessed by dereferencing it.
+pub const TMR_5_REGISTERS: *const Registers = (TMR + 0x60) as *const Registers;
+/// A pointer to the TMR6 register block that can be accessed by dereferencing it.
+pub const TMR_6_REGISTERS: *const Registers = (TMR + 0x68) as *const Registers;
+/// A pointer to the TMR7 register block that can be accessed by dereferencing it.
+pub const TMR_7_REGISTERS: *const Registers = (TMR + 0x70) as *const Registers;
+/// A pointer to the TMR8 register block that can be accessed by dereferencing it.
+pub const TMR_8_REGISTERS: *const Registers = (TMR + 0x78) as *const Registers;
+/// A pointer to the TMR9 register block that can be accessed by dereferencing it.
+pub const TMR_9_REGISTERS: *const Registers = (TMR + 0x80) as *const Registers;
+
+/// A pointer to the shared TMR register block that can be accessed by dereferencing it.
+pub const SHARED_REGISTERS: *const SharedRegisters = (TMR + 0x1A0) as *const SharedRegisters;
+
+register_bitfields! {
+    u32,
+
+    /// Bitfields of the `TIMER_TMR_PTV_0` register.
+    pub TIMER_TMR_PTV_0 [
+        /// Whether the timer should be enabled.
+        EN OFFSET(31) NUMBITS(1) [],
+
+        /// Whether the timer should be reloaded with the trigger value when it expires.
+        PER OFFSET(30) NUMBITS(1) [],
+
+        /// The trigger value that the timer counts down from, in microseconds.
+        TMR_PTV OFFSET(0) NUMBITS(29) []
+    ],
+
+    /// Bitfields of the `TIMER_TMR_PCR_0` register.
+    pub TIMER_TMR_PCR_0 [
+        /// Clears the interrupt of the timer when written with `1`.
+        INTR_CLR OFFSET(30) NUMBITS(1) [],
+
+        /// The current value of the timer, in microseconds.
+        TMR_PCV OFFSET(0) NUMBITS(29) []
+    ],
+
+    /// Bitfields of the `TIMER_TMR_SHARED_INTR_STATUS_0` register.
+    pub TIMER_TMR_SHARED_INTR_STATUS_0 [
+        /// The pending interrupts of TMR0 through TMR9, one bit per timer.
+        INTR_STATUS OFFSET(0) NUMBITS(10) []
+    ]
+}
+
+register_structs! {
+    /// Representation of the TMR registers.
+    #[allow(non_snake_case)]
+    pub Registers {
+        (0x00 => pub TIMER_TMR_PTV_0: ReadWrite<u32, TIMER_TMR_PTV_0::Register>),
+        (0x04 => pub TIMER_TMR_PCR_0: ReadWrite<u32, TIMER_TMR_PCR_0::Register>),
+        (0x08 => @END),
+    }
+}
+
+assert_eq_size!(Registers, [u8; 0x8]);
+
+register_structs! {
+    /// Representation of the shared TMR registers.
+    #[allow(non_snake_case)]
+    pub SharedRegisters {
+        (0x00 => pub TIMER_TMR_SHARED_INTR_STATUS_0: ReadWrite<u32, TIMER_TMR_SHARED_INTR_STATUS_0::Register>),
+        (0x04 => @END),
+    }
+}
+
+assert_eq_size!(SharedRegisters, [u8; 0x4]);
@@ -0,0 +1,93 @@
+//! Abstractions over the Watchdog Timer registers.
+//!
+//! See Chapter 8 in the Tegra X1 Technical Reference Manual for details.
+//!
+//! # Description
+//!
+//! Each of the five watchdog timers counts the expirations of a TMR timer
+//! that serves as its time base. Once the programmed amount of periods
+//! has passed without the watchdog being restarted, it escalates from an
+//! interrupt over an FIQ to the configured reset of the SoC.
+
+use register::{mmio::ReadWrite, register_bitfields, register_structs};
+
+use crate::memory_map::TMR;
+
+/// A pointer to the WDT0 register block that can be accessed by dereferencing it.
+pub const WDT_0_REGISTERS: *const Registers = (TMR + 0x100) as *const Registers;
+/// A pointer to the WDT1 register block that can be accessed by dereferencing it.
+pub const WDT_1_REGISTERS: *const Registers = (TMR + 0x120) as *const Registers;
+/// A pointer to the WDT2 register block that can be accessed by dereferencing it.
+pub const WDT_2_REGISTERS: *const Registers = (TMR + 0x140) as *const Registers;
+/// A pointer to the WDT3 register block that can be accessed by dereferencing it.
+pub const WDT_3_REGISTERS: *const Registers = (TMR + 0x160) as *const Registers;
+/// A pointer to the WDT4 register block that can be accessed by dereferencing it.
+pub const WDT_4_REGISTERS: *const Registers = (TMR + 0x180) as *const Registers;
+
+register_bitfields! {
+    u32,
+
+    /// Bitfields of the `TIMER_WDT_CONFIG_0` register.
+    pub TIMER_WDT_CONFIG_0 [
+        /// Whether the PMC should reset the whole system when the watchdog expires.
+        PMC2CAR_RST_EN OFFSET(15) NUMBITS(1) [],
+
+        /// Whether the CAR should reset the system when the watchdog expires.
+        SYS_RST_EN OFFSET(14) NUMBITS(1) [],
+
+        /// Whether an FIQ should be raised when the watchdog expires.
+        FIQ_EN OFFSET(13) NUMBITS(1) [],
+
+        /// Whether an interrupt should be raised when the watchdog expires.
+        INT_EN OFFSET(12) NUMBITS(1) [],
+
+        /// The number of expirations of the source timer that make up a watchdog period.
+        PERIOD OFFSET(4) NUMBITS(8) [],
+
+        /// The TMR timer that serves as the time base of the watchdog.
+        TIMER_SOURCE OFFSET(0) NUMBITS(4) []
+    ],
+
+    /// Bitfields of the `TIMER_WDT_STATUS_0` register.
+    pub TIMER_WDT_STATUS_0 [
+        /// The number of watchdog periods that have expired.
+        EXPIRATION_LEVEL OFFSET(12) NUMBITS(2) [],
+
+        /// The current value of the watchdog counter.
+        COUNT OFFSET(4) NUMBITS(8) []
+    ],
+
+    /// Bitfields of the `TIMER_WDT_COMMAND_0` register.
+    pub TIMER_WDT_COMMAND_0 [
+        /// Disables the watchdog counter.
+        ///
+        /// NOTE: The watchdog must be unlocked first.
+        DISABLE_COUNTER OFFSET(1) NUMBITS(1) [],
+
+        /// Starts or restarts the watchdog counter.
+        START_COUNTER OFFSET(0) NUMBITS(1) []
+    ],
+
+    /// Bitfields of the `TIMER_WDT_UNLOCK_PATTERN_0` register.
+    pub TIMER_WDT_UNLOCK_PATTERN_0 [
+        /// The pattern that unlocks disabling the watchdog counter.
+        UNLOCK_PATTERN OFFSET(0) NUMBITS(16) [
+            /// The pattern that must be written.
+            Unlock = 0xC45A
+        ]
+    ]
+}
+
+register_structs! {
+    /// Representation of the Watchdog Timer registers.
+    #[allow(non_snake_case)]
+    pub Registers {
+        (0x00 => pub TIMER_WDT_CONFIG_0: ReadWrite<u32, TIMER_WDT_CONFIG_0::Register>),
+        (0x04 => pub TIMER_WDT_STATUS_0: ReadWrite<u32, TIMER_WDT_STATUS_0::Register>),
+        (0x08 => pub TIMER_WDT_COMMAND_0: ReadWrite<u32, TIMER_WDT_COMMAND_0::Register>),
+        (0x0C => pub TIMER_WDT_UNLOCK_PATTERN_0: ReadWrite<u32, TIMER_WDT_UNLOCK_PATTERN_0::Register>),
+        (0x10 => @END),
+    }
+}
+
+assert_eq_size!(Registers, [u8; 0x10]);
@@ -164,6 +164,7 @@ assert_eq_size!(FalconExceptionClause, u8);
 /// Enumeration of potential Falcon processor exceptions
 /// that may occur when interacting with the TSEC.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum FalconError {
     /// The DMA engine timed out.
     DmaTimeout,
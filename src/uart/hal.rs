@@ -0,0 +1,62 @@
+//! Implementations of the [`embedded-hal-nb`] serial traits.
+//!
+//! [`embedded-hal-nb`]: https://docs.rs/embedded-hal-nb/1.0.0
+
+use embedded_hal_nb::{
+    nb,
+    serial::{ErrorKind, ErrorType, Read, Write},
+};
+
+use super::{Uart, UART_LSR_0};
+
+impl ErrorType for Uart {
+    type Error = ErrorKind;
+}
+
+impl Read<u8> for Uart {
+    fn read(&mut self) -> nb::Result<u8, ErrorKind> {
+        let controller = unsafe { &*self.registers };
+
+        // Reading the status clears the error bits.
+        let status = controller.UART_LSR_0.extract();
+
+        if status.is_set(UART_LSR_0::OVRF) {
+            return Err(nb::Error::Other(ErrorKind::Overrun));
+        } else if status.is_set(UART_LSR_0::PERR) {
+            return Err(nb::Error::Other(ErrorKind::Parity));
+        } else if status.is_set(UART_LSR_0::FERR) {
+            return Err(nb::Error::Other(ErrorKind::FrameFormat));
+        }
+
+        if !status.is_set(UART_LSR_0::RDR) {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        Ok(controller.UART_THR_DLAB_0_0.get() as u8)
+    }
+}
+
+impl Write<u8> for Uart {
+    fn write(&mut self, word: u8) -> nb::Result<(), ErrorKind> {
+        let controller = unsafe { &*self.registers };
+
+        if !controller.UART_LSR_0.is_set(UART_LSR_0::THRE) {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        controller.UART_THR_DLAB_0_0.set(word as u32);
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> nb::Result<(), ErrorKind> {
+        let controller = unsafe { &*self.registers };
+
+        // Wait for the TX FIFO and the shift register to drain.
+        if !controller.UART_LSR_0.is_set(UART_LSR_0::TMTY) {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        Ok(())
+    }
+}
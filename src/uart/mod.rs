@@ -87,12 +87,80 @@ use core::{
     marker::Sync,
 };
 
-use crate::{car::Clock, timer::usleep};
+use crate::{
+    car::Clock,
+    timer::{usleep, wait_until, TimeoutError},
+};
 
 pub use registers::*;
 
+#[cfg(feature = "embedded-hal-nb")]
+mod hal;
 mod registers;
 
+/// The rate of the PLLP-derived source clock of the UARTs, in Hz.
+const SOURCE_CLOCK_RATE: u32 = 408_000_000;
+
+/// The maximum tolerated deviation from a requested baud rate, in per mille.
+const BAUD_RATE_TOLERANCE: u32 = 20;
+
+/// The number of characters held by the TX FIFO and the shift register.
+const TX_CHARACTERS: u32 = 17;
+
+/// The maximum length of a character including start, parity and stop bits.
+const BITS_PER_CHARACTER: u32 = 12;
+
+/// Enumeration of potential errors that may occur when configuring a [`Uart`].
+///
+/// [`Uart`]: struct.Uart.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum UartError {
+    /// The baud rate cannot be generated from the source clock within
+    /// a tolerance of 2%.
+    InvalidBaudRate,
+    /// The pending data was not transmitted in time.
+    Timeout,
+}
+
+impl From<TimeoutError> for UartError {
+    fn from(_: TimeoutError) -> Self {
+        UartError::Timeout
+    }
+}
+
+/// Computes the divisor for generating a baud rate from the source clock.
+///
+/// The divisor is rounded to nearest. If the resulting baud rate deviates
+/// from the requested one by more than the tolerance, or the divisor doesn't
+/// fit into the divisor latch, [`UartError::InvalidBaudRate`] is returned.
+///
+/// [`UartError::InvalidBaudRate`]: enum.UartError.html#variant.InvalidBaudRate
+fn baud_divisor(baud_rate: u32) -> Result<u32, UartError> {
+    if baud_rate == 0 || baud_rate > SOURCE_CLOCK_RATE / 16 {
+        return Err(UartError::InvalidBaudRate);
+    }
+
+    // Calculate the divisor, rounded to nearest.
+    let divisor = (8 * baud_rate + SOURCE_CLOCK_RATE) / (16 * baud_rate);
+    if divisor > 0xFFFF {
+        return Err(UartError::InvalidBaudRate);
+    }
+
+    // Check the resulting baud rate against the tolerance.
+    let actual = SOURCE_CLOCK_RATE / (16 * divisor);
+    let deviation = if actual > baud_rate {
+        actual - baud_rate
+    } else {
+        baud_rate - actual
+    };
+    if u64::from(deviation) * 1000 > u64::from(baud_rate) * u64::from(BAUD_RATE_TOLERANCE) {
+        return Err(UartError::InvalidBaudRate);
+    }
+
+    Ok(divisor)
+}
+
 /// Representation of a UART.
 ///
 /// NOTE: Instances of this struct should never be created manually.
@@ -171,11 +239,36 @@ impl Uart {
         usleep((amount * 1_000_000 + baud_rate - 1) / baud_rate);
     }
 
+    /// Computes the time in microseconds it takes to drain a full TX FIFO,
+    /// with a safety margin of 100%.
+    fn drain_timeout_us(&self) -> u32 {
+        let baud_rate = self.baud.get().max(1);
+        2 * TX_CHARACTERS * BITS_PER_CHARACTER * 1_000_000 / baud_rate
+    }
+
     /// Computes the baud value that should be written to the MMIOs.
     fn get_baud_rate(&self) -> u32 {
         let baud_rate = self.baud.get();
 
-        (8 * baud_rate + 408_000_000) / (16 * baud_rate)
+        (8 * baud_rate + SOURCE_CLOCK_RATE) / (16 * baud_rate)
+    }
+
+    /// Programs the divisor latch with a given value.
+    fn set_divisor(&self, divisor: u32) {
+        let controller = unsafe { &*self.registers };
+
+        // Enable DLAB and set word length to 8.
+        controller.UART_LCR_0.modify(UART_LCR_0::DLAB::SET + UART_LCR_0::WD_SIZE::WordLength8);
+        // Divisor latch LSB.
+        controller.UART_THR_DLAB_0_0.set(divisor & 0xFF);
+        // Divisor latch MSB.
+        controller.UART_IER_DLAB_0_0.set((divisor >> 8) & 0xFF);
+        // Disable DLAB.
+        controller.UART_LCR_0.modify(UART_LCR_0::DLAB::CLEAR);
+        // Dummy read.
+        controller.UART_SPR_0.get();
+        // Wait 3 symbols for the new baud rate.
+        self.wait_symbols(3);
     }
 
     /// Initializes the Uart with a given baud rate.
@@ -209,18 +302,8 @@ impl Uart {
         controller.UART_IER_DLAB_0_0.set(0);
         // Disable hardware flow control.
         controller.UART_MCR_0.set(0);
-        // Enable DLAB and set word length to 8.
-        controller.UART_LCR_0.modify(UART_LCR_0::DLAB::SET + UART_LCR_0::WD_SIZE::WordLength8);
-        // Divisor latch LSB.
-        controller.UART_THR_DLAB_0_0.set(real_baud_rate);
-        // Divisor latch MSB.
-        controller.UART_IER_DLAB_0_0.set(real_baud_rate >> 8);
-        // Disable DLAB.
-        controller.UART_LCR_0.modify(UART_LCR_0::DLAB::CLEAR);
-        // Dummy read.
-        controller.UART_SPR_0.get();
-        // Wait 3 symbols for the new baud rate.
-        self.wait_symbols(3);
+        // Program the divisor latch.
+        self.set_divisor(real_baud_rate);
 
         // Enable FIFO with default settings.
 
@@ -235,6 +318,39 @@ impl Uart {
         self.flush();
     }
 
+    /// Changes the baud rate of an initialized Uart.
+    ///
+    /// The divisor is derived from the 408MHz PLLP source clock and rounded
+    /// to nearest. Baud rates that cannot be generated within a tolerance of
+    /// 2%, e.g. because they exceed the maximum rate of 25.5Mbaud, are rejected
+    /// with [`UartError::InvalidBaudRate`] and leave the Uart untouched.
+    ///
+    /// NOTE: This waits for pending data to be transmitted before switching
+    /// to the new baud rate. If the data isn't drained in the time a full TX
+    /// FIFO takes at the current baud rate, e.g. because hardware flow control
+    /// holds it back, [`UartError::Timeout`] is returned and the Uart is left
+    /// untouched.
+    ///
+    /// [`UartError::InvalidBaudRate`]: enum.UartError.html#variant.InvalidBaudRate
+    /// [`UartError::Timeout`]: enum.UartError.html#variant.Timeout
+    pub fn set_baud_rate(&self, baud_rate: u32) -> Result<(), UartError> {
+        let controller = unsafe { &*self.registers };
+
+        let divisor = baud_divisor(baud_rate)?;
+
+        // Wait for idle state.
+        wait_until(
+            || controller.UART_LSR_0.is_set(UART_LSR_0::TMTY),
+            self.drain_timeout_us(),
+        )?;
+
+        // Store the provided baud rate and program the divisor latch.
+        self.baud.set(baud_rate);
+        self.set_divisor(divisor);
+
+        Ok(())
+    }
+
     /// Reads a byte over UART and returns it.
     ///
     /// This method blocks until data is available to read.
@@ -251,18 +367,13 @@ impl Uart {
 
     /// Fills a mutable slice of data with bytes read over UART.
     ///
-    /// This method blocks until the buffer is filled.
+    /// This method blocks until the buffer is filled and returns
+    /// as soon as the last byte was read from the RX FIFO.
     pub fn read(&self, data: &mut [u8]) {
-        let controller = unsafe { &*self.registers };
-
         // Read the bytes one by one into the buffer.
         for i in data.iter_mut() {
             *i = self.read_byte();
         }
-
-        while !controller.UART_LSR_0.is_set(UART_LSR_0::RDR) {
-            // Wait for everything to be read.
-        }
     }
 
     /// Writes a byte over UART.
@@ -340,3 +451,39 @@ impl Write for Uart {
 }
 
 unsafe impl Sync for Uart {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn baud_divisor_rounds_to_nearest() {
+        assert_eq!(baud_divisor(115_200), Ok(221));
+        assert_eq!(baud_divisor(9600), Ok(2656));
+        assert_eq!(baud_divisor(1_500_000), Ok(17));
+
+        // 25.5 is rounded up, which still is within the tolerance.
+        assert_eq!(baud_divisor(1_000_000), Ok(26));
+    }
+
+    #[test]
+    fn baud_divisor_covers_the_divisor_latch() {
+        assert_eq!(baud_divisor(SOURCE_CLOCK_RATE / 16), Ok(1));
+        assert_eq!(baud_divisor(SOURCE_CLOCK_RATE / 16 + 1), Err(UartError::InvalidBaudRate));
+
+        assert_eq!(baud_divisor(390), Ok(65385));
+        assert_eq!(baud_divisor(389), Err(UartError::InvalidBaudRate));
+        assert_eq!(baud_divisor(0), Err(UartError::InvalidBaudRate));
+    }
+
+    #[test]
+    fn baud_divisor_enforces_the_tolerance() {
+        // The divisor of 2 yields 12.75Mbaud, which is within 2% of these.
+        assert_eq!(baud_divisor(12_500_000), Ok(2));
+        assert_eq!(baud_divisor(13_010_204), Ok(2));
+
+        assert_eq!(baud_divisor(12_499_999), Err(UartError::InvalidBaudRate));
+        assert_eq!(baud_divisor(13_010_205), Err(UartError::InvalidBaudRate));
+        assert_eq!(baud_divisor(20_000_000), Err(UartError::InvalidBaudRate));
+    }
+}